@@ -0,0 +1,16 @@
+use std::env;
+use std::path::PathBuf;
+
+use arete_codegen::{Backend, EngineCatalogue};
+
+// The loader bindings don't depend on what a module declares, so they're generated from an empty
+// one and stay in step with the ABI arete-codegen emits.
+fn main() {
+    let module = arete_codegen::parse_source("", "", EngineCatalogue::default()).unwrap();
+    let bindings = module.generate(&Backend::RustBindings).unwrap();
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    arete_codegen::write_if_changed(&out_dir.join("bindings.rs"), &bindings).unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+
+use crate::bindings::{
+    ComponentId, EngineCallbacks, EntityId, ModuleApi, ModuleStatus, QueryForEachCallback,
+    QueryParForEachCallback,
+};
+use crate::storage::World;
+use crate::Error;
+
+thread_local! {
+    // the last exception the module reported, picked up when the call it escaped from fails
+    static REPORTED_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn take_reported_error() -> Option<String> {
+    REPORTED_ERROR.with(|reported| reported.borrow_mut().take())
+}
+
+// What a query argument's handle points to while its system runs.
+pub(crate) struct QueryState {
+    pub(crate) world: *const World,
+    pub(crate) terms: Vec<ComponentId>,
+}
+
+impl QueryState {
+    unsafe fn from_handle<'a>(handle: *const c_void) -> (&'a Self, &'a World) {
+        let query = &*(handle as *const QueryState);
+        (query, &*query.world)
+    }
+
+    fn get(&self, world: &World, entity: EntityId, component: ComponentId) -> *mut u8 {
+        if !self.terms.contains(&component) {
+            return ptr::null_mut();
+        }
+
+        let matches = world.locations.get(&entity).is_some_and(|&(archetype, _)| {
+            world.archetypes[archetype]
+                .columns_for(&self.terms)
+                .is_some()
+        });
+
+        if !matches {
+            return ptr::null_mut();
+        }
+
+        world
+            .component(entity, component)
+            .unwrap_or(ptr::null_mut())
+    }
+
+    fn get_first(&self, world: &World, component: ComponentId) -> *mut u8 {
+        if !self.terms.contains(&component) {
+            return ptr::null_mut();
+        }
+
+        world
+            .archetypes
+            .iter()
+            .filter(|archetype| !archetype.entities.is_empty())
+            .find(|archetype| archetype.columns_for(&self.terms).is_some())
+            .and_then(|archetype| {
+                let column = archetype.ids.binary_search(&component).ok()?;
+                Some(archetype.columns[column].ptr(0))
+            })
+            .unwrap_or(ptr::null_mut())
+    }
+
+    fn len(&self, world: &World) -> usize {
+        world
+            .archetypes
+            .iter()
+            .filter(|archetype| archetype.columns_for(&self.terms).is_some())
+            .map(|archetype| archetype.entities.len())
+            .sum()
+    }
+
+    fn for_each(&self, world: &World, mut f: impl FnMut(*mut *mut c_void)) {
+        let mut components = vec![ptr::null_mut(); self.terms.len()];
+
+        for archetype in &world.archetypes {
+            let Some(columns) = archetype.columns_for(&self.terms) else {
+                continue;
+            };
+
+            for row in 0..archetype.entities.len() {
+                for (component, &column) in components.iter_mut().zip(&columns) {
+                    *component = archetype.columns[column].ptr(row) as *mut c_void;
+                }
+
+                f(components.as_mut_ptr());
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn query_get(
+    handle: *const c_void,
+    entity: EntityId,
+    component: ComponentId,
+) -> *const c_void {
+    let (query, world) = QueryState::from_handle(handle);
+    query.get(world, entity, component) as *const c_void
+}
+
+unsafe extern "C" fn query_get_mut(
+    handle: *mut c_void,
+    entity: EntityId,
+    component: ComponentId,
+) -> *mut c_void {
+    let (query, world) = QueryState::from_handle(handle);
+    query.get(world, entity, component) as *mut c_void
+}
+
+unsafe extern "C" fn query_get_first(
+    handle: *const c_void,
+    component: ComponentId,
+) -> *const c_void {
+    let (query, world) = QueryState::from_handle(handle);
+    query.get_first(world, component) as *const c_void
+}
+
+unsafe extern "C" fn query_get_first_mut(
+    handle: *mut c_void,
+    component: ComponentId,
+) -> *mut c_void {
+    let (query, world) = QueryState::from_handle(handle);
+    query.get_first(world, component) as *mut c_void
+}
+
+unsafe extern "C" fn query_for_each(
+    handle: *mut c_void,
+    callback: QueryForEachCallback,
+    user_data: *mut c_void,
+) {
+    let (query, world) = QueryState::from_handle(handle);
+    query.for_each(world, |components| unsafe {
+        callback(user_data, components)
+    });
+}
+
+// runs serially, which is a valid schedule for any parallel iteration
+unsafe extern "C" fn query_par_for_each(
+    handle: *mut c_void,
+    callback: QueryParForEachCallback,
+    user_data: *const c_void,
+) {
+    let (query, world) = QueryState::from_handle(handle);
+    query.for_each(world, |components| unsafe {
+        callback(user_data, components)
+    });
+}
+
+unsafe extern "C" fn query_len(handle: *const c_void) -> usize {
+    let (query, world) = QueryState::from_handle(handle);
+    query.len(world)
+}
+
+unsafe extern "C" fn query_is_empty(handle: *const c_void) -> bool {
+    let (query, world) = QueryState::from_handle(handle);
+    query.len(world) == 0
+}
+
+unsafe extern "C" fn query_single(handle: *mut c_void, components: *mut *mut c_void) -> usize {
+    let (query, world) = QueryState::from_handle(handle);
+
+    let len = query.len(world);
+    if len == 1 {
+        query.for_each(world, |single| unsafe {
+            ptr::copy_nonoverlapping(single, components, query.terms.len());
+        });
+    }
+
+    len
+}
+
+unsafe extern "C" fn report_error(context: *const c_char, message: *const c_char) {
+    let context = CStr::from_ptr(context).to_string_lossy();
+    let message = CStr::from_ptr(message).to_string_lossy();
+
+    REPORTED_ERROR.with(|reported| *reported.borrow_mut() = Some(format!("{context}: {message}")));
+}
+
+// The host leaves only the profiling callbacks out.
+pub(crate) fn install(api: &ModuleApi) -> Result<(), Error> {
+    let callbacks = EngineCallbacks {
+        query_get: Some(query_get),
+        query_get_mut: Some(query_get_mut),
+        query_get_first: Some(query_get_first),
+        query_get_first_mut: Some(query_get_first_mut),
+        query_for_each: Some(query_for_each),
+        query_par_for_each: Some(query_par_for_each),
+        report_error: Some(report_error),
+        query_len: Some(query_len),
+        query_is_empty: Some(query_is_empty),
+        query_single: Some(query_single),
+        ..EngineCallbacks::new()
+    };
+
+    let status = unsafe { api.module_init(&callbacks) };
+    if status != ModuleStatus::Ok {
+        return Err(Error::Init {
+            status,
+            reason: api
+                .module_last_error()
+                .map(|reason| reason.to_string_lossy().into_owned()),
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,504 @@
+//! Headless host for Arete game modules: loads a compiled module, backs its queries with simple
+//! archetype storage and runs its systems, without the rest of the engine.
+//!
+//! ```no_run
+//! use arete_codegen::EngineCatalogue;
+//! use arete_host::Host;
+//!
+//! let catalogue = EngineCatalogue::load("engine.json".as_ref())?;
+//! let mut host = Host::load("libgame.so", &catalogue)?;
+//! host.run(60)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod callbacks;
+mod storage;
+
+pub mod bindings {
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+}
+
+use std::alloc::Layout;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::mem::{self, MaybeUninit};
+use std::path::Path;
+use std::{fmt, ptr};
+
+use arete_codegen::EngineCatalogue;
+use libloading::Library;
+
+use crate::bindings::{
+    expected_abi_hash, ArgType, ComponentId, ComponentType, EntityId, MissingSymbol, ModuleApi,
+    ModuleStatus, SystemFnPtr,
+};
+use crate::callbacks::QueryState;
+use crate::storage::{Column, World};
+
+#[derive(Debug)]
+pub enum Error {
+    Load(libloading::Error),
+    MissingSymbol(MissingSymbol),
+    EngineVersion {
+        module: u32,
+        engine: u32,
+    },
+    // the module was generated against a different ABI or public component layouts
+    AbiHash {
+        module: u64,
+        engine: u64,
+    },
+    // the module rejected the engine's callbacks, with the message it left for `module_last_error`
+    Init {
+        status: ModuleStatus,
+        reason: Option<String>,
+    },
+    // a system takes an engine resource the catalogue gives no layout for
+    UnknownLayout(String),
+    // `reason` is the exception the module reported or, for systems, the name of the error code
+    ResourceInit {
+        string_id: String,
+        code: i32,
+        reason: Option<String>,
+    },
+    System {
+        name: String,
+        code: i32,
+        reason: Option<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Load(err) => write!(f, "failed to load game module: {err}"),
+            Self::MissingSymbol(err) => write!(f, "{err}"),
+            Self::EngineVersion { module, engine } => write!(
+                f,
+                "game module targets engine version {module}, but the catalogue is for {engine}"
+            ),
+            Self::AbiHash { module, engine } => write!(
+                f,
+                "game module has ABI hash {module:016x}, but the engine expects {engine:016x}"
+            ),
+            Self::Init { status, reason } => {
+                write!(f, "game module failed to initialize with {status:?}")?;
+                write_reason(f, reason)
+            }
+            Self::UnknownLayout(string_id) => {
+                write!(f, "the engine catalogue has no layout for {string_id}")
+            }
+            Self::ResourceInit {
+                string_id,
+                code,
+                reason,
+            } => {
+                write!(f, "initializing {string_id} failed with {code}")?;
+                write_reason(f, reason)
+            }
+            Self::System { name, code, reason } => {
+                write!(f, "system {name} failed with {code}")?;
+                write_reason(f, reason)
+            }
+        }
+    }
+}
+
+fn write_reason(f: &mut fmt::Formatter, reason: &Option<String>) -> fmt::Result {
+    match reason {
+        Some(reason) => write!(f, " ({reason})"),
+        None => Ok(()),
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<libloading::Error> for Error {
+    fn from(err: libloading::Error) -> Self {
+        Self::Load(err)
+    }
+}
+
+impl From<MissingSymbol> for Error {
+    fn from(err: MissingSymbol) -> Self {
+        Self::MissingSymbol(err)
+    }
+}
+
+// Component ids are handed out in order of first use and passed on to the module.
+#[derive(Default)]
+struct Registry {
+    layouts: Vec<Option<Layout>>,
+    ids: HashMap<String, ComponentId>,
+}
+
+impl Registry {
+    fn register(
+        &mut self,
+        api: &ModuleApi,
+        string_id: &CStr,
+        layout: Option<Layout>,
+    ) -> ComponentId {
+        let key = string_id.to_str().unwrap();
+
+        if let Some(&id) = self.ids.get(key) {
+            let known = &mut self.layouts[id as usize];
+
+            match (*known, layout) {
+                (Some(known), Some(layout)) => assert_eq!(
+                    known, layout,
+                    "layout of {key} doesn't match the one it was registered with"
+                ),
+                (None, layout) => *known = layout,
+                _ => {}
+            }
+
+            return id;
+        }
+
+        let id = self.layouts.len() as ComponentId;
+        self.layouts.push(layout);
+        self.ids.insert(key.to_owned(), id);
+        api.set_component_id(string_id, id);
+
+        id
+    }
+
+    fn id(&self, string_id: &str) -> Option<ComponentId> {
+        self.ids.get(string_id).copied()
+    }
+}
+
+enum SystemArg {
+    Resource(ComponentId),
+    Query(Vec<ComponentId>),
+}
+
+struct System {
+    name: String,
+    function: SystemFnPtr,
+    is_once: bool,
+    args: Vec<SystemArg>,
+}
+
+// Component ids and callbacks are set on the loaded library, which the OS shares across the process,
+// so only one host per module should be alive at a time.
+pub struct Host {
+    api: ModuleApi,
+    registry: Registry,
+    systems: Vec<System>,
+    world: World,
+    frame: u64,
+    // dropped last, everything above may point into it
+    _library: Library,
+}
+
+impl Host {
+    pub fn load(path: impl AsRef<Path>, catalogue: &EngineCatalogue) -> Result<Self, Error> {
+        let library = unsafe { Library::new(path.as_ref())? };
+
+        let api = unsafe {
+            ModuleApi::load(|name| {
+                library
+                    .get::<*const c_void>(name.to_bytes_with_nul())
+                    .map(|symbol| *symbol)
+                    .unwrap_or(ptr::null())
+            })?
+        };
+
+        if let Some(engine) = catalogue.engine_version {
+            let module = api.arete_target_version();
+            if module != engine {
+                return Err(Error::EngineVersion { module, engine });
+            }
+        }
+
+        // without every layout from the catalogue the engine can't tell what the module expects
+        let public: Option<Vec<_>> = catalogue
+            .public
+            .iter()
+            .map(|public| Some((public.string_id.as_str(), public.size?, public.align?)))
+            .collect();
+
+        if let Some(public) = public {
+            let engine = unsafe { expected_abi_hash(api.arete_module_descriptor(), &public) };
+            let module = api.arete_abi_hash();
+            if module != engine {
+                return Err(Error::AbiHash { module, engine });
+            }
+        }
+
+        callbacks::install(&api)?;
+
+        let mut host = Self {
+            api,
+            registry: Registry::default(),
+            systems: Vec::new(),
+            world: World::default(),
+            frame: 0,
+            _library: library,
+        };
+
+        host.register_components(catalogue)?;
+        host.register_systems()?;
+
+        Ok(host)
+    }
+
+    fn register_components(&mut self, catalogue: &EngineCatalogue) -> Result<(), Error> {
+        let api = &self.api;
+
+        for public in &catalogue.public {
+            let layout = public
+                .size
+                .zip(public.align)
+                .map(|(size, align)| Layout::from_size_align(size, align).unwrap());
+
+            let string_id = CString::new(public.string_id.as_str()).unwrap();
+            self.registry.register(api, &string_id, layout);
+        }
+
+        for index in 0.. {
+            let Some(string_id) = api.component_string_id(index) else {
+                break;
+            };
+
+            let layout = Layout::from_size_align(
+                api.component_size(string_id),
+                api.component_align(string_id),
+            )
+            .unwrap();
+
+            let id = self.registry.register(api, string_id, Some(layout));
+
+            if api.component_type(string_id) == ComponentType::Resource {
+                // resources are never destroyed, the module only gets to construct them
+                let mut column = Column::new(layout);
+                let value = column.push_uninit();
+
+                let code = unsafe { api.resource_init(string_id, value as *mut c_void) };
+                if code != 0 {
+                    return Err(Error::ResourceInit {
+                        string_id: string_id.to_string_lossy().into_owned(),
+                        code,
+                        reason: callbacks::take_reported_error(),
+                    });
+                }
+
+                self.world.resources.insert(id, column);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn register_systems(&mut self) -> Result<(), Error> {
+        let api = &self.api;
+
+        for system in 0..api.systems_len() {
+            let mut args = Vec::new();
+
+            for arg in 0..api.system_args_len(system) {
+                if api.system_arg_type(system, arg) == ArgType::Query {
+                    let terms = (0..api.system_query_args_len(system, arg))
+                        .map(|term| {
+                            let string_id = api.system_query_arg_component(system, arg, term);
+                            self.registry.register(api, string_id.unwrap(), None)
+                        })
+                        .collect();
+
+                    args.push(SystemArg::Query(terms));
+                    continue;
+                }
+
+                let string_id = api.system_arg_component(system, arg).unwrap();
+                let id = self.registry.register(api, string_id, None);
+
+                // engine resources start zeroed unless the caller inserts them first
+                if !self.world.resources.contains_key(&id) {
+                    let Some(layout) = self.registry.layouts[id as usize] else {
+                        return Err(Error::UnknownLayout(
+                            string_id.to_string_lossy().into_owned(),
+                        ));
+                    };
+
+                    let mut column = Column::new(layout);
+                    unsafe { ptr::write_bytes(column.push_uninit(), 0, layout.size()) };
+                    self.world.resources.insert(id, column);
+                }
+
+                args.push(SystemArg::Resource(id));
+            }
+
+            self.systems.push(System {
+                name: api
+                    .system_name(system)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned(),
+                function: api.system_fn(system),
+                is_once: api.system_is_once(system),
+                args,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn api(&self) -> &ModuleApi {
+        &self.api
+    }
+
+    // number of frames run so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn entities_len(&self) -> usize {
+        self.world.locations.len()
+    }
+
+    pub fn component_id(&self, string_id: &str) -> Option<ComponentId> {
+        self.registry.id(string_id)
+    }
+
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        EntityBuilder {
+            host: self,
+            components: Vec::new(),
+        }
+    }
+
+    pub fn get<T: Copy>(&self, entity: EntityId, string_id: &str) -> Option<T> {
+        let id = self.typed_id::<T>(string_id)?;
+        let value = self.world.component(entity, id)?;
+
+        Some(unsafe { ptr::read(value as *const T) })
+    }
+
+    pub fn resource<T: Copy>(&self, string_id: &str) -> Option<T> {
+        let id = self.typed_id::<T>(string_id)?;
+        let value = self.world.resource(id)?;
+
+        Some(unsafe { ptr::read(value as *const T) })
+    }
+
+    pub fn insert_resource<T: Copy>(&mut self, string_id: &str, value: T) {
+        let string_id = CString::new(string_id).unwrap();
+        let id = self
+            .registry
+            .register(&self.api, &string_id, Some(Layout::new::<T>()));
+
+        let mut column = Column::new(Layout::new::<T>());
+        unsafe { column.push(&value as *const T as *const u8) };
+        self.world.resources.insert(id, column);
+    }
+
+    fn typed_id<T>(&self, string_id: &str) -> Option<ComponentId> {
+        let id = self.registry.id(string_id)?;
+
+        if let Some(layout) = self.registry.layouts[id as usize] {
+            assert_eq!(
+                layout,
+                Layout::new::<T>(),
+                "{} doesn't have the layout of {string_id}",
+                std::any::type_name::<T>()
+            );
+        }
+
+        Some(id)
+    }
+
+    pub fn run(&mut self, frames: u64) -> Result<(), Error> {
+        for _ in 0..frames {
+            for (index, system) in self.systems.iter().enumerate() {
+                if system.is_once && self.frame > 0 {
+                    continue;
+                }
+
+                let queries: Vec<_> = system
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        SystemArg::Query(terms) => Some(QueryState {
+                            world: &self.world,
+                            terms: terms.clone(),
+                        }),
+                        SystemArg::Resource(_) => None,
+                    })
+                    .collect();
+
+                let mut queries = queries.iter();
+
+                let mut args: Vec<_> = system
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        SystemArg::Resource(id) => self.world.resource(*id).unwrap() as *mut c_void,
+                        SystemArg::Query(_) => {
+                            queries.next().unwrap() as *const QueryState as *mut c_void
+                        }
+                    })
+                    .collect();
+
+                let code = unsafe { (system.function)(args.as_mut_ptr()) };
+                if code != 0 {
+                    let reason = callbacks::take_reported_error().or_else(|| {
+                        self.api
+                            .system_error_name(index, code)
+                            .map(|name| name.to_string_lossy().into_owned())
+                    });
+
+                    return Err(Error::System {
+                        name: system.name.clone(),
+                        code,
+                        reason,
+                    });
+                }
+            }
+
+            self.frame += 1;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct EntityBuilder<'a> {
+    host: &'a mut Host,
+    components: Vec<(ComponentId, Layout, Vec<MaybeUninit<u8>>)>,
+}
+
+impl EntityBuilder<'_> {
+    pub fn with<T: Copy>(mut self, string_id: &str, value: T) -> Self {
+        let layout = Layout::new::<T>();
+
+        let string_id = CString::new(string_id).unwrap();
+        let id = self
+            .host
+            .registry
+            .register(&self.host.api, &string_id, Some(layout));
+
+        let mut bytes = vec![MaybeUninit::uninit(); mem::size_of::<T>()];
+        unsafe {
+            ptr::copy_nonoverlapping(
+                &value as *const T as *const MaybeUninit<u8>,
+                bytes.as_mut_ptr(),
+                bytes.len(),
+            );
+        }
+
+        self.components.push((id, layout, bytes));
+        self
+    }
+
+    pub fn id(self) -> EntityId {
+        let components = self
+            .components
+            .iter()
+            .map(|(id, layout, bytes)| (*id, *layout, bytes.as_ptr() as *const u8))
+            .collect();
+
+        unsafe { self.host.world.spawn(components) }
+    }
+}
@@ -0,0 +1,47 @@
+use std::error::Error;
+use std::path::Path;
+use std::process;
+
+use arete_codegen::EngineCatalogue;
+use arete_host::Host;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(author, version)]
+struct Args {
+    /// Compiled game module to load
+    module: String,
+
+    /// Number of frames to run the module's systems for
+    #[arg(short, long, default_value_t = 1)]
+    frames: u64,
+
+    /// Public component catalogue shipped with the engine, giving the layouts of engine resources
+    #[arg(short, long)]
+    engine_manifest: Option<String>,
+}
+
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let catalogue = match &args.engine_manifest {
+        Some(path) => EngineCatalogue::load(Path::new(path))?,
+        None => EngineCatalogue::default(),
+    };
+
+    let mut host = Host::load(&args.module, &catalogue)?;
+    host.run(args.frames)?;
+
+    println!(
+        "ran {} systems for {} frames",
+        host.api().systems_len(),
+        host.frame()
+    );
+
+    Ok(())
+}
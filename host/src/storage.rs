@@ -0,0 +1,174 @@
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::ptr::{self, NonNull};
+
+use crate::bindings::{ComponentId, EntityId};
+
+// Type-erased storage for values of one layout. The buffer is only reached through a raw pointer,
+// so the module can write to components while the host holds shared references to the world.
+pub(crate) struct Column {
+    item: Layout,
+    data: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+}
+
+impl Column {
+    pub(crate) fn new(layout: Layout) -> Self {
+        let item = layout.pad_to_align();
+
+        Self {
+            item,
+            data: NonNull::new(ptr::without_provenance_mut(item.align())).unwrap(),
+            len: 0,
+            capacity: if item.size() == 0 { usize::MAX } else { 0 },
+        }
+    }
+
+    pub(crate) fn ptr(&self, row: usize) -> *mut u8 {
+        debug_assert!(row < self.len);
+        self.data.as_ptr().wrapping_add(row * self.item.size())
+    }
+
+    /// # Safety
+    ///
+    /// `src` must point to a value with the column's layout.
+    pub(crate) unsafe fn push(&mut self, src: *const u8) {
+        if self.len == self.capacity {
+            self.grow();
+        }
+
+        ptr::copy_nonoverlapping(
+            src,
+            self.data.as_ptr().add(self.len * self.item.size()),
+            self.item.size(),
+        );
+        self.len += 1;
+    }
+
+    // leaves the new value uninitialized, for the module to construct in place
+    pub(crate) fn push_uninit(&mut self) -> *mut u8 {
+        if self.len == self.capacity {
+            self.grow();
+        }
+
+        self.len += 1;
+        self.ptr(self.len - 1)
+    }
+
+    fn grow(&mut self) {
+        let capacity = (self.capacity * 2).max(4);
+        let layout = self.array_layout(capacity);
+
+        let data = unsafe {
+            if self.capacity == 0 {
+                alloc::alloc(layout)
+            } else {
+                alloc::realloc(
+                    self.data.as_ptr(),
+                    self.array_layout(self.capacity),
+                    layout.size(),
+                )
+            }
+        };
+
+        self.data = NonNull::new(data).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        self.capacity = capacity;
+    }
+
+    fn array_layout(&self, capacity: usize) -> Layout {
+        Layout::from_size_align(self.item.size() * capacity, self.item.align()).unwrap()
+    }
+}
+
+impl Drop for Column {
+    fn drop(&mut self) {
+        if self.item.size() != 0 && self.capacity != 0 {
+            unsafe { alloc::dealloc(self.data.as_ptr(), self.array_layout(self.capacity)) }
+        }
+    }
+}
+
+// every entity with exactly the same set of components, one column per component
+pub(crate) struct Archetype {
+    pub(crate) ids: Vec<ComponentId>,
+    pub(crate) columns: Vec<Column>,
+    pub(crate) entities: Vec<EntityId>,
+}
+
+impl Archetype {
+    // index of the column holding each of `ids`, if the archetype has all of them
+    pub(crate) fn columns_for(&self, ids: &[ComponentId]) -> Option<Vec<usize>> {
+        ids.iter()
+            .map(|id| self.ids.binary_search(id).ok())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct World {
+    pub(crate) archetypes: Vec<Archetype>,
+    // archetype and row of each entity
+    pub(crate) locations: HashMap<EntityId, (usize, usize)>,
+    pub(crate) resources: HashMap<ComponentId, Column>,
+    next_entity: EntityId,
+}
+
+impl World {
+    /// # Safety
+    ///
+    /// Each pointer must point to a value with the layout paired with it.
+    pub(crate) unsafe fn spawn(
+        &mut self,
+        mut components: Vec<(ComponentId, Layout, *const u8)>,
+    ) -> EntityId {
+        components.sort_by_key(|(id, ..)| *id);
+        components.dedup_by_key(|(id, ..)| *id);
+
+        let ids: Vec<_> = components.iter().map(|(id, ..)| *id).collect();
+
+        let index = match self.archetypes.iter().position(|a| a.ids == ids) {
+            Some(index) => index,
+            None => {
+                self.archetypes.push(Archetype {
+                    columns: components
+                        .iter()
+                        .map(|(_, layout, _)| Column::new(*layout))
+                        .collect(),
+                    ids,
+                    entities: Vec::new(),
+                });
+
+                self.archetypes.len() - 1
+            }
+        };
+
+        let archetype = &mut self.archetypes[index];
+
+        for (column, (_, _, src)) in archetype.columns.iter_mut().zip(&components) {
+            column.push(*src);
+        }
+
+        // 0 is left free as a null entity
+        self.next_entity += 1;
+        let entity = self.next_entity;
+
+        archetype.entities.push(entity);
+        self.locations
+            .insert(entity, (index, archetype.entities.len() - 1));
+
+        entity
+    }
+
+    pub(crate) fn component(&self, entity: EntityId, id: ComponentId) -> Option<*mut u8> {
+        let &(archetype, row) = self.locations.get(&entity)?;
+        let archetype = &self.archetypes[archetype];
+        let column = archetype.ids.binary_search(&id).ok()?;
+
+        Some(archetype.columns[column].ptr(row))
+    }
+
+    pub(crate) fn resource(&self, id: ComponentId) -> Option<*mut u8> {
+        self.resources.get(&id).map(|column| column.ptr(0))
+    }
+}
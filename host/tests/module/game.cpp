@@ -0,0 +1,16 @@
+#include "game.h"
+
+void start(Stats& stats) {
+    stats.started += 1;
+}
+
+void integrate(Query<Transform&, const Velocity&> movers, Stats& stats, const Time& time) {
+    stats.frames += 1;
+
+    movers.for_each([&](Transform& transform, const Velocity& velocity) {
+        transform.position[0] += velocity.x * time.delta;
+        transform.position[1] += velocity.y * time.delta;
+        transform.position[2] += velocity.z * time.delta;
+        stats.moved += 1;
+    });
+}
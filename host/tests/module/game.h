@@ -0,0 +1,18 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z;
+};
+COMPONENT(Velocity);
+
+struct Stats {
+    uint32_t frames;
+    uint32_t moved;
+    uint32_t started;
+};
+RESOURCE(Stats);
+
+SYSTEM_ONCE(void start, Stats& stats);
+SYSTEM(void integrate, Query<Transform&, const Velocity&> movers, Stats& stats, const Time& time);
@@ -0,0 +1,227 @@
+// Builds tests/module against mock/arete.h with `$CXX` (or `c++`), then drives it through the host.
+// Skipped when no compiler is found, unless `ARETE_REQUIRE_CXX` is set.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use arete_codegen::{Backend, CppOptions, EngineCatalogue};
+use arete_host::{Error, Host};
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform {
+    position: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Velocity {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Time {
+    delta: f32,
+    elapsed: f64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stats {
+    frames: u32,
+    moved: u32,
+    started: u32,
+}
+
+fn transform(x: f32) -> Transform {
+    Transform {
+        position: [x, 0.0, 0.0],
+        rotation: [0.0, 0.0, 0.0, 1.0],
+        scale: [1.0; 3],
+    }
+}
+
+// The module is built once per run. Its component ids and callbacks are process-wide, so tests also
+// take turns loading it.
+fn module() -> Option<(MutexGuard<'static, ()>, EngineCatalogue, PathBuf)> {
+    static MODULE: OnceLock<Option<(EngineCatalogue, PathBuf)>> = OnceLock::new();
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    let (catalogue, library) = MODULE
+        .get_or_init(|| {
+            let catalogue = catalogue();
+            build_module("host-module", &catalogue).map(|library| (catalogue, library))
+        })
+        .clone()?;
+    let guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    Some((guard, catalogue, library))
+}
+
+fn catalogue() -> EngineCatalogue {
+    let mock = Path::new(env!("CARGO_MANIFEST_DIR")).join("../mock");
+    EngineCatalogue::load(&mock.join("engine.json")).unwrap()
+}
+
+fn build_module(name: &str, catalogue: &EngineCatalogue) -> Option<PathBuf> {
+    let cxx = common::compiler()?;
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mock = root.join("../mock");
+    let module_dir = root.join("tests/module");
+
+    let source = fs::read_to_string(module_dir.join("game.h")).unwrap();
+    let module = arete_codegen::parse_source(&source, "game.h", catalogue.clone()).unwrap();
+
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let ffi = module
+        .generate(&Backend::Cpp {
+            include: String::from("game.h"),
+            options: CppOptions::default(),
+        })
+        .unwrap();
+    fs::write(out_dir.join("ffi.cpp"), ffi).unwrap();
+
+    let library = out_dir.join("libgame.so");
+
+    let output = Command::new(&cxx)
+        .args(["-std=c++20", "-shared", "-fPIC", "-Wall", "-Werror"])
+        .arg("-I")
+        .arg(&module_dir)
+        .arg("-I")
+        .arg(&mock)
+        .arg(out_dir.join("ffi.cpp"))
+        .arg(module_dir.join("game.cpp"))
+        .arg("-o")
+        .arg(&library)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "test module failed to build:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Some(library)
+}
+
+#[test]
+fn runs_systems_over_spawned_entities() {
+    let Some((_guard, catalogue, library)) = module() else {
+        return;
+    };
+
+    let mut host = Host::load(&library, &catalogue).unwrap();
+
+    host.insert_resource(
+        "arete_public::Time",
+        Time {
+            delta: 0.5,
+            elapsed: 0.0,
+        },
+    );
+
+    let moving = host
+        .spawn()
+        .with("arete_public::Transform", transform(1.0))
+        .with(
+            "game_module::Velocity",
+            Velocity {
+                x: 2.0,
+                y: -4.0,
+                z: 0.0,
+            },
+        )
+        .id();
+
+    // not matched by the query, so never moves
+    let still = host
+        .spawn()
+        .with("arete_public::Transform", transform(5.0))
+        .id();
+
+    host.run(3).unwrap();
+
+    assert_eq!(host.frame(), 3);
+    assert_eq!(host.entities_len(), 2);
+
+    let stats: Stats = host.resource("game_module::Stats").unwrap();
+    assert_eq!(
+        stats,
+        Stats {
+            frames: 3,
+            moved: 3,
+            started: 1,
+        }
+    );
+
+    let moved: Transform = host.get(moving, "arete_public::Transform").unwrap();
+    assert_eq!(moved.position, [4.0, -6.0, 0.0]);
+
+    let velocity: Velocity = host.get(moving, "game_module::Velocity").unwrap();
+    assert_eq!(velocity.x, 2.0);
+
+    let unmoved: Transform = host.get(still, "arete_public::Transform").unwrap();
+    assert_eq!(unmoved, transform(5.0));
+    assert_eq!(host.get::<Velocity>(still, "game_module::Velocity"), None);
+}
+
+#[test]
+fn engine_resources_start_zeroed() {
+    let Some((_guard, catalogue, library)) = module() else {
+        return;
+    };
+
+    let mut host = Host::load(&library, &catalogue).unwrap();
+    host.spawn()
+        .with("arete_public::Transform", transform(1.0))
+        .with(
+            "game_module::Velocity",
+            Velocity {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        )
+        .id();
+
+    host.run(1).unwrap();
+
+    let time: Time = host.resource("arete_public::Time").unwrap();
+    assert_eq!(time.delta, 0.0);
+
+    let stats: Stats = host.resource("game_module::Stats").unwrap();
+    assert_eq!(stats.moved, 1);
+}
+
+#[test]
+fn rejects_module_with_different_abi_hash() {
+    // generated against a catalogue where Time had another string id, so the engine doesn't know
+    // its layout and leaves it out of the hash
+    let mut stale = catalogue();
+    for public in &mut stale.public {
+        if public.ident == "Time" {
+            public.string_id = String::from("arete_public::OldTime");
+        }
+    }
+
+    let Some(library) = build_module("host-module-stale", &stale) else {
+        return;
+    };
+
+    let err = Host::load(&library, &catalogue()).err().unwrap();
+    assert!(matches!(err, Error::AbiHash { .. }), "{err}");
+}
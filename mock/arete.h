@@ -0,0 +1,143 @@
+// Minimal stand-in for the engine's arete.h, declaring only what modules and the generated ffi.cpp
+// rely on. It deliberately includes nothing beyond <cstdint>, so generated code that forgets one of
+// its own includes fails to compile against it. The public types match mock/engine.json.
+
+#pragma once
+
+#include <cstdint>
+
+#define ENGINE_VERSION 1
+
+typedef uint32_t ComponentId;
+typedef uint64_t EntityId;
+
+typedef void (*QueryForEachCallback)(void* user_data, void** components);
+typedef void (*QueryParForEachCallback)(const void* user_data, void** components);
+
+// zero is success, any other code is reported to the engine as the system's error
+struct AreteResult {
+    int32_t code;
+
+    static AreteResult ok() {
+        return { 0 };
+    }
+
+    static AreteResult error(int32_t code) {
+        return { code };
+    }
+};
+
+template <typename T>
+struct Component {
+    static inline ComponentId ID = 0;
+};
+
+// set by the engine through the generated module_init
+extern void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+
+namespace arete_mock {
+
+template <typename T>
+struct RemoveReference {
+    using Type = T;
+};
+
+template <typename T>
+struct RemoveReference<T&> {
+    using Type = T;
+};
+
+template <unsigned... Is>
+struct Indices {};
+
+template <unsigned N, unsigned... Is>
+struct MakeIndices : MakeIndices<N - 1, N - 1, Is...> {};
+
+template <unsigned... Is>
+struct MakeIndices<0, Is...> {
+    using Type = Indices<Is...>;
+};
+
+} // namespace arete_mock
+
+template <typename... Ts>
+struct Query {
+    void* handle;
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachFn(handle, for_each_callback<F>, &f);
+    }
+
+private:
+    template <typename F>
+    static void for_each_callback(void* user_data, void** components) {
+        invoke(*static_cast<F*>(user_data), components, typename arete_mock::MakeIndices<sizeof...(Ts)>::Type{});
+    }
+
+    template <typename F, unsigned... Is>
+    static void invoke(F& f, void** components, arete_mock::Indices<Is...>) {
+        f(*static_cast<typename arete_mock::RemoveReference<Ts>::Type*>(components[Is])...);
+    }
+};
+
+// public components
+
+struct Camera {
+    float fov;
+    float near_plane;
+    float far_plane;
+};
+
+struct Color {
+    float r, g, b, a;
+};
+
+struct DirectionalLight {
+    float direction[3];
+    float intensity;
+};
+
+struct DynamicStaticMesh {
+    uint64_t mesh;
+};
+
+struct PointLight {
+    float position[3];
+    float intensity;
+};
+
+struct Transform {
+    float position[3];
+    float rotation[4];
+    float scale[3];
+};
+
+// public resources
+
+struct Input {
+    uint8_t keys[256];
+};
+
+struct Time {
+    float delta;
+    double elapsed;
+};
+
+struct Window {
+    uint32_t width, height;
+};
+
+#define COMPONENT(T)
+#define RESOURCE(T)
+
+// With ARETE_MOCK_DEFINE_SYSTEMS every system gets an empty body, so a module links without its
+// implementation files. Those bodies never run, so systems returning a value may fall off the end.
+#ifdef ARETE_MOCK_DEFINE_SYSTEMS
+#pragma GCC diagnostic ignored "-Wreturn-type"
+#define SYSTEM(decl, ...) inline decl(__VA_ARGS__) {}
+#define SYSTEM_ONCE(decl, ...) inline decl(__VA_ARGS__) {}
+#else
+#define SYSTEM(decl, ...) decl(__VA_ARGS__)
+#define SYSTEM_ONCE(decl, ...) decl(__VA_ARGS__)
+#endif
@@ -0,0 +1,415 @@
+use crate::rust::{camel_case, rust_fn_ptr, rust_ident, rust_type};
+use crate::{ArgType, SystemInputInfo};
+
+// engine-side declarations the module ABI refers to
+pub(crate) const ENGINE_TYPES: &[AbiType] = &[
+    AbiType::Alias {
+        ident: "ComponentId",
+        ty: "uint32_t",
+    },
+    AbiType::Alias {
+        ident: "EntityId",
+        ty: "uint64_t",
+    },
+    AbiType::FnPtr {
+        ident: "QueryForEachCallback",
+        ret: "void",
+        args: "void*, void**",
+    },
+    AbiType::FnPtr {
+        ident: "QueryParForEachCallback",
+        ret: "void",
+        args: "const void*, void**",
+    },
+];
+
+// New callbacks are only ever appended, so an older engine's smaller `EngineCallbacks` is still
+// a prefix of the current layout.
+pub(crate) struct CallbackInfo {
+    // the global the generated code calls through
+    pub ident: &'static str,
+    // its `EngineCallbacks` field
+    pub field: &'static str,
+    pub fn_ptr: &'static str,
+    // needed by any module whose systems take queries, everything else may be left null
+    pub required: bool,
+    pub ret: &'static str,
+    pub args: &'static str,
+}
+
+// bumped when the meaning of an existing callback changes, rather than when one is added
+pub(crate) const ENGINE_CALLBACKS_VERSION: u32 = 1;
+
+pub(crate) const CALLBACKS: &[CallbackInfo] = &[
+    CallbackInfo {
+        ident: "QueryGetFn",
+        field: "query_get",
+        fn_ptr: "query_get_fn",
+        required: true,
+        ret: "const void*",
+        args: "const void*, EntityId, ComponentId",
+    },
+    CallbackInfo {
+        ident: "QueryGetMutFn",
+        field: "query_get_mut",
+        fn_ptr: "query_get_mut_fn",
+        required: true,
+        ret: "void*",
+        args: "void*, EntityId, ComponentId",
+    },
+    CallbackInfo {
+        ident: "QueryGetFirstFn",
+        field: "query_get_first",
+        fn_ptr: "query_get_first_fn",
+        required: true,
+        ret: "const void*",
+        args: "const void*, ComponentId",
+    },
+    CallbackInfo {
+        ident: "QueryGetFirstMutFn",
+        field: "query_get_first_mut",
+        fn_ptr: "query_get_first_mut_fn",
+        required: true,
+        ret: "void*",
+        args: "void*, ComponentId",
+    },
+    CallbackInfo {
+        ident: "QueryForEachFn",
+        field: "query_for_each",
+        fn_ptr: "query_for_each_fn",
+        required: true,
+        ret: "void",
+        args: "void*, QueryForEachCallback, void*",
+    },
+    CallbackInfo {
+        ident: "QueryParForEachFn",
+        field: "query_par_for_each",
+        fn_ptr: "query_par_for_each_fn",
+        required: true,
+        ret: "void",
+        args: "void*, QueryParForEachCallback, const void*",
+    },
+    // the system name or resource string id a C++ exception escaped from, and its `what()`
+    CallbackInfo {
+        ident: "ReportErrorFn",
+        field: "report_error",
+        fn_ptr: "report_error_fn",
+        required: false,
+        ret: "void",
+        args: "const char*, const char*",
+    },
+    // only called by modules generated with profiling, around each system by index
+    CallbackInfo {
+        ident: "ProfileBeginFn",
+        field: "profile_begin",
+        fn_ptr: "profile_begin_fn",
+        required: false,
+        ret: "void",
+        args: "size_t",
+    },
+    CallbackInfo {
+        ident: "ProfileEndFn",
+        field: "profile_end",
+        fn_ptr: "profile_end_fn",
+        required: false,
+        ret: "void",
+        args: "size_t",
+    },
+    CallbackInfo {
+        ident: "QueryLenFn",
+        field: "query_len",
+        fn_ptr: "query_len_fn",
+        required: false,
+        ret: "size_t",
+        args: "const void*",
+    },
+    CallbackInfo {
+        ident: "QueryIsEmptyFn",
+        field: "query_is_empty",
+        fn_ptr: "query_is_empty_fn",
+        required: false,
+        ret: "bool",
+        args: "const void*",
+    },
+    // fills in one component per term and returns 1 when exactly one entity matches, otherwise
+    // returns the number of matches without touching the components, which modules report
+    CallbackInfo {
+        ident: "QuerySingleFn",
+        field: "query_single",
+        fn_ptr: "query_single_fn",
+        required: false,
+        ret: "size_t",
+        args: "void*, void**",
+    },
+];
+
+// a type declared by the module ABI, in C syntax
+pub(crate) enum AbiType {
+    Alias {
+        ident: &'static str,
+        ty: &'static str,
+    },
+    FnPtr {
+        ident: &'static str,
+        ret: &'static str,
+        args: &'static str,
+    },
+    Enum {
+        ident: &'static str,
+        variants: Vec<&'static str>,
+    },
+    Struct {
+        ident: &'static str,
+        fields: Vec<(&'static str, &'static str)>,
+    },
+    Const {
+        ident: &'static str,
+        value: u32,
+    },
+}
+
+pub(crate) fn arg_type_name(input: &SystemInputInfo) -> &'static str {
+    match &input.arg_type {
+        ArgType::DataAccessDirect if input.mutable => "ArgTypeDataAccessMut",
+        ArgType::DataAccessDirect => "ArgTypeDataAccessRef",
+        ArgType::DataAccessCell => "ArgTypeDataAccessRef",
+        ArgType::Query { .. } => "ArgTypeQuery",
+    }
+}
+
+pub(crate) fn abi_types() -> Vec<AbiType> {
+    vec![
+        AbiType::Enum {
+            ident: "ComponentType",
+            variants: vec!["Component", "Resource"],
+        },
+        AbiType::Enum {
+            ident: "ArgType",
+            variants: vec!["DataAccessMut", "DataAccessRef", "Query"],
+        },
+        AbiType::Enum {
+            ident: "ModuleStatus",
+            variants: vec![
+                "Ok",
+                "UnknownComponent",
+                "UnknownSystem",
+                "UnknownArg",
+                "UnknownQueryTerm",
+                "WrongArgType",
+                "MissingCallback",
+                "UnsupportedCallbacks",
+            ],
+        },
+        AbiType::FnPtr {
+            ident: "system_fn_ptr",
+            ret: "int32_t",
+            args: "void**",
+        },
+        AbiType::FnPtr {
+            ident: "system_error_name_fn",
+            ret: "const char*",
+            args: "int32_t",
+        },
+        AbiType::FnPtr {
+            ident: "entity_map_fn",
+            ret: "EntityId",
+            args: "EntityId",
+        },
+    ]
+    .into_iter()
+    .chain(CALLBACKS.iter().map(|callback| AbiType::FnPtr {
+        ident: callback.fn_ptr,
+        ret: callback.ret,
+        args: callback.args,
+    }))
+    .chain([
+        AbiType::Struct {
+            ident: "EntityField",
+            fields: vec![("size_t", "offset"), ("size_t", "len")],
+        },
+        AbiType::Struct {
+            ident: "SourceLocation",
+            fields: vec![("const char*", "file"), ("uint32_t", "line")],
+        },
+        AbiType::Struct {
+            ident: "ComponentDescriptor",
+            fields: vec![
+                ("const char*", "string_id"),
+                ("size_t", "size"),
+                ("size_t", "align"),
+                ("ComponentType", "component_type"),
+                ("size_t", "entity_fields_len"),
+                ("const EntityField*", "entity_fields"),
+                ("const char*", "name"),
+                ("SourceLocation", "source"),
+            ],
+        },
+        AbiType::Struct {
+            ident: "QueryTermDescriptor",
+            fields: vec![("ArgType", "arg_type"), ("const char*", "component")],
+        },
+        AbiType::Struct {
+            ident: "ArgDescriptor",
+            fields: vec![
+                ("ArgType", "arg_type"),
+                ("const char*", "component"),
+                ("ComponentType", "component_type"),
+                ("size_t", "query_terms_len"),
+                ("const QueryTermDescriptor*", "query_terms"),
+            ],
+        },
+        AbiType::Struct {
+            ident: "SystemDescriptor",
+            fields: vec![
+                ("system_fn_ptr", "fn"),
+                ("bool", "is_once"),
+                ("size_t", "args_len"),
+                ("const ArgDescriptor*", "args"),
+                ("system_error_name_fn", "error_name"),
+                ("const char*", "name"),
+                ("SourceLocation", "source"),
+            ],
+        },
+        AbiType::Struct {
+            ident: "ModuleDescriptor",
+            fields: vec![
+                ("uint32_t", "engine_version"),
+                ("size_t", "components_len"),
+                ("const ComponentDescriptor*", "components"),
+                ("size_t", "systems_len"),
+                ("const SystemDescriptor*", "systems"),
+            ],
+        },
+        AbiType::Const {
+            ident: "ENGINE_CALLBACKS_VERSION",
+            value: ENGINE_CALLBACKS_VERSION,
+        },
+        // `size` is `sizeof(EngineCallbacks)` as the engine was built, fields past it are null
+        AbiType::Struct {
+            ident: "EngineCallbacks",
+            fields: [("uint32_t", "size"), ("uint32_t", "version")]
+                .into_iter()
+                .chain(CALLBACKS.iter().map(|c| (c.fn_ptr, c.field)))
+                .collect(),
+        },
+    ])
+    .collect()
+}
+
+// shared by the implementation and the C header, so it must stay valid C as well as C++
+pub(crate) fn gen_types() -> String {
+    let mut output = String::new();
+
+    for abi_type in abi_types() {
+        output += &abi_type.gen_c();
+        output += "\n";
+    }
+
+    output
+}
+
+impl AbiType {
+    pub(crate) fn gen_rust(&self) -> String {
+        let mut output = String::new();
+
+        match self {
+            AbiType::Alias { ident, ty } => {
+                output += &format!("pub type {ident} = {};\n", rust_type(ty));
+            }
+            AbiType::FnPtr { ident, ret, args } => {
+                output += &format!(
+                    "pub type {} = {};\n",
+                    camel_case(ident),
+                    rust_fn_ptr(ret, args)
+                );
+            }
+            // A Rust enum holding a value it doesn't declare is undefined behaviour, and a newer
+            // module may return one, so C enums stay integers with a constant per enumerator.
+            AbiType::Enum { ident, variants } => {
+                output += "#[repr(transparent)]\n";
+                output += "#[derive(Clone, Copy, PartialEq, Eq, Hash)]\n";
+                output += &format!("pub struct {ident}(pub i32);\n\n");
+
+                output += "#[allow(non_upper_case_globals)]\n";
+                output += &format!("impl {ident} {{\n");
+                for (value, variant) in variants.iter().enumerate() {
+                    output += &format!("    pub const {variant}: Self = Self({value});\n");
+                }
+                output += "}\n\n";
+
+                output += &format!("impl std::fmt::Debug for {ident} {{\n");
+                output +=
+                    "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n";
+                output += "        match *self {\n";
+                for variant in variants {
+                    output +=
+                        &format!("            Self::{variant} => f.write_str(\"{variant}\"),\n");
+                }
+                output +=
+                    &format!("            Self(value) => write!(f, \"{ident}({{value}})\"),\n");
+                output += "        }\n";
+                output += "    }\n";
+                output += "}\n";
+            }
+            AbiType::Struct { ident, fields } => {
+                output += "#[repr(C)]\n";
+                output += "#[derive(Debug, Clone, Copy)]\n";
+                output += &format!("pub struct {ident} {{\n");
+                for (ty, field) in fields.iter() {
+                    // function pointers may be null in C
+                    let rust_ty = if is_fn_ptr(ty) {
+                        format!("Option<{}>", rust_type(ty))
+                    } else {
+                        rust_type(ty)
+                    };
+                    output += &format!("    pub {}: {rust_ty},\n", rust_ident(field));
+                }
+                output += "}\n";
+            }
+            AbiType::Const { ident, value } => {
+                output += &format!("pub const {ident}: u32 = {value};\n");
+            }
+        }
+
+        output
+    }
+
+    pub(crate) fn gen_c(&self) -> String {
+        let mut output = String::new();
+
+        match self {
+            AbiType::Alias { ident, ty } => {
+                output += &format!("typedef {ty} {ident};\n");
+            }
+            AbiType::FnPtr { ident, ret, args } => {
+                output += &format!("typedef {ret} (*{ident})({args});\n");
+            }
+            AbiType::Enum { ident, variants } => {
+                output += &format!("typedef enum {ident} {{\n");
+                for variant in variants {
+                    output += &format!("    {ident}{variant},\n");
+                }
+                output += &format!("}} {ident};\n");
+            }
+            AbiType::Struct { ident, fields } => {
+                output += &format!("typedef struct {ident} {{\n");
+                for (ty, field) in fields.iter() {
+                    output += &format!("    {ty} {field};\n");
+                }
+                output += &format!("}} {ident};\n");
+            }
+            AbiType::Const { ident, value } => {
+                output += &format!("#define {ident} {value}\n");
+            }
+        }
+
+        output
+    }
+}
+
+fn is_fn_ptr(ty: &str) -> bool {
+    ENGINE_TYPES
+        .iter()
+        .chain(&abi_types())
+        .any(|abi_type| matches!(abi_type, AbiType::FnPtr { ident, .. } if *ident == ty))
+}
@@ -0,0 +1,1240 @@
+use regex::Regex;
+
+use crate::abi::{arg_type_name, gen_types, CALLBACKS};
+use crate::hash::{abi_hash, abi_hash_layouts, gen_string_id_hash, gen_string_id_lookup};
+use crate::{
+    ArgType, CppOptions, FfiGenerator, ReturnType, SourceLocation, StructType, SystemInfo,
+    SystemInputInfo,
+};
+
+impl FfiGenerator {
+    pub(crate) fn gen_ffi(&self, header: String, options: &CppOptions) -> String {
+        let mut output = header;
+
+        output += &self.gen_version();
+        // only the string id lookups use it, and they're empty without components
+        if !self.structs.is_empty() || !self.public_inputs().is_empty() {
+            output += &gen_string_id_hash();
+        }
+        output += &gen_types();
+        output += &self.gen_last_error();
+        output += &self.gen_callbacks(options);
+        output += &self.gen_components();
+        output += &self.gen_resource_init(options);
+        output += &self.gen_systems(options);
+        output += &self.gen_module_descriptor();
+        output += &self.gen_abi_hash();
+
+        if options.embed_manifest {
+            output += &self.gen_manifest_export();
+        }
+
+        output
+    }
+
+    // one raw literal per line keeps each literal well within compiler length limits
+    fn gen_manifest_export(&self) -> String {
+        let mut output = String::new();
+
+        let manifest = serde_json::to_string_pretty(&self.manifest()).unwrap();
+
+        output += "extern \"C\" const char* arete_module_manifest() {\n";
+        output += "    return\n";
+        for line in manifest.lines() {
+            output += &format!("        R\"arete_manifest({line})arete_manifest\" \"\\n\"\n");
+        }
+        output += "        ;\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    // Signatures of every `extern "C"` function in the generated implementation. The optional
+    // `arete_module_manifest` export isn't part of the ABI, engines look it up by name.
+    pub(crate) fn exports(&self) -> Vec<String> {
+        let re = Regex::new("(?m)^extern \"C\" ([^{]+?)\\s*\\{").unwrap();
+        re.captures_iter(&self.gen_ffi(String::new(), &CppOptions::default()))
+            .map(|export| export[1].to_owned())
+            .collect()
+    }
+
+    fn gen_components(&self) -> String {
+        let mut output = String::new();
+
+        output += &self.gen_component_descriptors();
+        output += &self.gen_component_string_id();
+        output += &self.gen_component_size();
+        output += &self.gen_component_align();
+        output += &self.gen_component_type();
+        output += &self.gen_component_name();
+        output += &self.gen_component_source_location();
+        output += &self.gen_set_component_ids();
+        output += &self.gen_component_entity_fields();
+        output += &self.gen_component_remap_entities();
+
+        output
+    }
+
+    fn gen_component_descriptors(&self) -> String {
+        let mut output = String::new();
+
+        for struct_info in &self.structs {
+            if let StructType::Component = struct_info.struct_type {
+                output += &format!(
+                    "static_assert(std::is_standard_layout_v<{}>);\n",
+                    struct_info.ident
+                );
+                output += &format!(
+                    "static_assert(std::is_trivially_copyable_v<{}>);\n",
+                    struct_info.ident
+                );
+            }
+        }
+
+        if !output.is_empty() {
+            output += "\n";
+        }
+
+        for struct_info in self.structs.iter().filter(|s| s.has_entity_fields()) {
+            output += &format!(
+                "static const EntityField {}_ENTITY_FIELDS[] = {{\n",
+                struct_info.ident
+            );
+
+            for field in struct_info.fields.iter().filter(|f| f.is_entity()) {
+                output += &format!(
+                    "    {{ offsetof({}, {}), {} }},\n",
+                    struct_info.ident,
+                    field.ident,
+                    field.array_len.as_deref().unwrap_or("1")
+                );
+            }
+
+            output += "};\n\n";
+        }
+
+        if self.structs.is_empty() {
+            return output;
+        }
+
+        output += "static const ComponentDescriptor COMPONENTS[] = {\n";
+
+        for struct_info in &self.structs {
+            let component_type = match struct_info.struct_type {
+                StructType::Component => "ComponentTypeComponent",
+                StructType::Resource => "ComponentTypeResource",
+            };
+
+            output += "    {\n";
+            output += &format!("        \"{}\",\n", struct_info.string_id);
+            output += &format!("        sizeof({}),\n", struct_info.ident);
+            output += &format!("        alignof({}),\n", struct_info.ident);
+            output += &format!("        {component_type},\n");
+
+            if struct_info.has_entity_fields() {
+                output += &format!(
+                    "        sizeof({0}_ENTITY_FIELDS) / sizeof(EntityField),\n",
+                    struct_info.ident
+                );
+                output += &format!("        {}_ENTITY_FIELDS,\n", struct_info.ident);
+            } else {
+                output += "        0,\n";
+                output += "        nullptr,\n";
+            }
+
+            output += &format!("        \"{}\",\n", struct_info.ident);
+            output += &format!("        {},\n", gen_source_location(&struct_info.source));
+            output += "    },\n";
+        }
+
+        output += "};\n\n";
+
+        output
+    }
+
+    fn gen_component_string_id(&self) -> String {
+        let mut output = String::new();
+
+        let string_ids: Vec<_> = self.structs.iter().map(|s| s.string_id.as_str()).collect();
+        output += &gen_string_id_lookup(
+            "component_index",
+            "COMPONENT",
+            &string_ids,
+            "COMPONENTS[index].string_id",
+        );
+
+        output += "extern \"C\" const char* component_string_id(size_t index) {\n";
+
+        if self.structs.is_empty() {
+            output += "    return nullptr;\n";
+        } else {
+            output += &format!("    if (index >= {}) {{\n", self.structs.len());
+            output += "        return nullptr;\n";
+            output += "    }\n\n";
+            output += "    return COMPONENTS[index].string_id;\n";
+        }
+
+        output += "}\n\n";
+
+        output
+    }
+
+    fn gen_component_lookup(&self, ret: &str, name: &str, field: &str) -> String {
+        self.gen_component_fallible(
+            ret,
+            name,
+            &["const char* string_id"],
+            vec![
+                format!("*out = COMPONENTS[index].{field};"),
+                String::from("return ModuleStatusOk;"),
+            ],
+        )
+    }
+
+    // `found` runs with the string id's `index` into `COMPONENTS`, unknown ones fail
+    fn gen_component_fallible(
+        &self,
+        ret: &str,
+        name: &str,
+        params: &[&str],
+        found: Vec<String>,
+    ) -> String {
+        let unknown = String::from(
+            "return fail(ModuleStatusUnknownComponent, \"unknown component %s\", string_id);",
+        );
+
+        let body = if self.structs.is_empty() {
+            vec![unknown]
+        } else {
+            let mut body = vec![
+                String::from("int32_t index = component_index(string_id);"),
+                String::from("if (index < 0) {"),
+                format!("    {unknown}"),
+                String::from("}"),
+                String::new(),
+            ];
+            body.extend(found);
+            body
+        };
+
+        self.gen_fallible(ret, name, params, &body)
+    }
+
+    fn gen_component_size(&self) -> String {
+        self.gen_component_lookup("size_t", "component_size", "size")
+    }
+
+    fn gen_component_align(&self) -> String {
+        self.gen_component_lookup("size_t", "component_align", "align")
+    }
+
+    fn gen_component_type(&self) -> String {
+        self.gen_component_lookup("ComponentType", "component_type", "component_type")
+    }
+
+    fn gen_component_name(&self) -> String {
+        self.gen_component_lookup("const char*", "component_name", "name")
+    }
+
+    fn gen_component_source_location(&self) -> String {
+        self.gen_component_lookup("SourceLocation", "component_source_location", "source")
+    }
+
+    fn gen_set_component_ids(&self) -> String {
+        struct ComponentInfo<'a> {
+            ident: &'a str,
+            string_id: String,
+        }
+
+        let mut components: Vec<_> = self
+            .structs
+            .iter()
+            .map(|s| ComponentInfo {
+                ident: &s.ident,
+                string_id: s.string_id.clone(),
+            })
+            .chain(self.public_inputs().into_iter().map(|s| ComponentInfo {
+                ident: &s.ident,
+                string_id: s.string_id.clone(),
+            }))
+            .collect();
+
+        components.sort_unstable_by(|a, b| a.ident.cmp(b.ident));
+        components.dedup_by(|a, b| a.ident == b.ident);
+
+        let mut output = String::new();
+
+        output += "struct ComponentIdSlot {\n";
+        output += "    const char* string_id;\n";
+        output += "    ComponentId* id;\n";
+        output += "};\n\n";
+
+        output += "static const ComponentIdSlot COMPONENT_ID_SLOTS[] = {\n";
+
+        for component in &components {
+            output += &format!(
+                "    {{ \"{}\", &Component<{}>::ID }},\n",
+                component.string_id, component.ident
+            );
+        }
+
+        output += "};\n\n";
+
+        let string_ids: Vec<_> = components.iter().map(|c| c.string_id.as_str()).collect();
+        output += &gen_string_id_lookup(
+            "component_id_index",
+            "COMPONENT_ID",
+            &string_ids,
+            "COMPONENT_ID_SLOTS[index].string_id",
+        );
+
+        output += "extern \"C\" ModuleStatus try_set_component_id(const char* string_id, ComponentId id) {\n";
+        output += "    int32_t index = component_id_index(string_id);\n";
+        output += "    if (index < 0) {\n";
+        output += "        return fail(ModuleStatusUnknownComponent, \"unknown component %s\", string_id);\n";
+        output += "    }\n\n";
+        output += "    *COMPONENT_ID_SLOTS[index].id = id;\n";
+        output += "    return ModuleStatusOk;\n";
+        output += "}\n\n";
+
+        // the engine hands every module all of its ids, most of which a module won't use
+        output += "extern \"C\" void set_component_id(const char* string_id, ComponentId id) {\n";
+        output += "    int32_t index = component_id_index(string_id);\n";
+        output += "    if (index >= 0) {\n";
+        output += "        *COMPONENT_ID_SLOTS[index].id = id;\n";
+        output += "    }\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    fn gen_component_entity_fields(&self) -> String {
+        self.gen_component_fallible(
+            "const EntityField*",
+            "component_entity_fields",
+            &["const char* string_id", "size_t* len"],
+            vec![
+                String::from("*len = COMPONENTS[index].entity_fields_len;"),
+                String::from("*out = COMPONENTS[index].entity_fields;"),
+                String::from("return ModuleStatusOk;"),
+            ],
+        )
+    }
+
+    // components without entity fields are left alone
+    fn gen_component_remap_entities(&self) -> String {
+        let mut found = vec![String::from("switch (index) {")];
+
+        for (i, struct_info) in self.structs.iter().enumerate() {
+            if !struct_info.has_entity_fields() {
+                continue;
+            }
+
+            found.push(format!("    case {i}: {{"));
+            found.push(format!(
+                "        {0}* component = static_cast<{0}*>(val);",
+                struct_info.ident
+            ));
+
+            for field in struct_info.fields.iter().filter(|f| f.is_entity()) {
+                if let Some(len) = &field.array_len {
+                    found.push(format!("        for (size_t i = 0; i < {len}; ++i) {{"));
+                    found.push(format!(
+                        "            component->{0}[i] = map_fn(component->{0}[i]);",
+                        field.ident
+                    ));
+                    found.push(String::from("        }"));
+                } else {
+                    found.push(format!(
+                        "        component->{0} = map_fn(component->{0});",
+                        field.ident
+                    ));
+                }
+            }
+
+            found.push(String::from("        break;"));
+            found.push(String::from("    }"));
+        }
+
+        found.push(String::from("    default: break;"));
+        found.push(String::from("}"));
+        found.push(String::new());
+        found.push(String::from("return ModuleStatusOk;"));
+
+        self.gen_component_fallible(
+            "void",
+            "component_remap_entities",
+            &["const char* string_id", "void* val", "entity_map_fn map_fn"],
+            found,
+        )
+    }
+
+    // Resources are constructed in place, with exceptions reported rather than crossing the
+    // boundary. `*out` is 0 when construction succeeded, an unknown string id or one that isn't a
+    // resource fails.
+    fn gen_resource_init(&self, options: &CppOptions) -> String {
+        let mut output = String::new();
+
+        let resources: Vec<_> = self
+            .structs
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.struct_type == StructType::Resource)
+            .collect();
+
+        if !resources.is_empty() {
+            let body =
+                String::from("    std::construct_at(static_cast<T*>(val));\n    return 0;\n");
+
+            output += "template <typename T>\n";
+            output += "static int32_t construct_resource(const char* string_id, void* val) {\n";
+            output += &gen_exception_guard(options, "string_id", body);
+            output += "}\n\n";
+        }
+
+        let mut body = vec![String::from("switch (component_index(string_id)) {")];
+
+        for (i, struct_info) in resources {
+            body.push(format!("    case {i}:"));
+            body.push(format!(
+                "        *out = construct_resource<{}>(string_id, val);",
+                struct_info.ident
+            ));
+            body.push(String::from("        return ModuleStatusOk;"));
+        }
+
+        body.push(String::from("    default:"));
+        body.push(String::from(
+            "        return fail(ModuleStatusUnknownComponent, \"unknown resource %s\", string_id);",
+        ));
+        body.push(String::from("}"));
+
+        output += &self.gen_fallible(
+            "int32_t",
+            "resource_init",
+            &["const char* string_id", "void* val"],
+            &body,
+        );
+
+        output
+    }
+
+    fn gen_systems(&self, options: &CppOptions) -> String {
+        let mut output = String::new();
+
+        output += &self.gen_system_fn_ffi(options);
+        output += &self.gen_system_descriptors();
+
+        output += &self.gen_systems_len();
+        output += &self.gen_system_name();
+        output += &self.gen_system_source_location();
+        output += &self.gen_system_is_once();
+        output += &self.gen_system_fn();
+        output += &self.gen_system_error_name();
+        output += &self.gen_system_args_len();
+        output += &self.gen_system_arg_type();
+        output += &self.gen_system_arg_component();
+
+        output += &self.gen_system_query_args_len();
+        output += &self.gen_system_query_arg_type();
+        output += &self.gen_system_query_arg_component();
+
+        output
+    }
+
+    fn gen_system_fn_ffi(&self, options: &CppOptions) -> String {
+        let mut output = String::new();
+
+        let gen_system_fn = &mut |index: usize, system: &SystemInfo| {
+            if let ReturnType::Enum { ident, .. } = &system.return_type {
+                output += &format!(
+                    "static_assert(std::is_enum_v<{ident}>, \"system {} must return void, bool, AreteResult or an error enum\");\n\n",
+                    system.ident
+                );
+            }
+
+            let mut body = String::new();
+
+            body += match &system.return_type {
+                ReturnType::Void => "    ",
+                ReturnType::Bool => "    const bool ok = ",
+                ReturnType::AreteResult => "    const AreteResult result = ",
+                ReturnType::Enum { .. } => "    const auto error = ",
+            };
+            body += &system.ident;
+            body += "(\n";
+
+            for (i, input) in system.inputs.iter().enumerate() {
+                if let ArgType::Query { .. } = &input.arg_type {
+                    body += &format!("        {{ input[{i}] }}")
+                } else {
+                    body += "        *static_cast<";
+                    if !input.mutable {
+                        body += "const ";
+                    }
+                    body += &format!("{}*>(input[{i}])", input.ident);
+                }
+
+                if i + 1 < system.inputs.len() {
+                    body += ",\n";
+                } else {
+                    body += "\n";
+                }
+            }
+
+            body += "    );\n\n";
+            body += match &system.return_type {
+                ReturnType::Void => "    return 0;\n",
+                ReturnType::Bool => "    return ok ? 0 : 1;\n",
+                ReturnType::AreteResult => "    return result.code;\n",
+                ReturnType::Enum { .. } => "    return static_cast<int32_t>(error);\n",
+            };
+
+            output += "int32_t ";
+            output += &system.ident;
+            output += "_ffi(void** input) {\n";
+            if options.profile {
+                output += &format!("    ProfileScope profile({index});\n\n");
+            }
+            output += &gen_exception_guard(options, &format!("\"{}\"", system.ident), body);
+            output += "}\n\n";
+
+            let names: Vec<_> = match &system.return_type {
+                ReturnType::Bool => vec![(String::from("1"), "false")],
+                ReturnType::Enum { ident, variants } => variants
+                    .iter()
+                    .map(|variant| {
+                        (
+                            format!("static_cast<int32_t>({ident}::{variant})"),
+                            variant.as_str(),
+                        )
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            if names.is_empty() {
+                return;
+            }
+
+            // The enumerators' values aren't known here, so rather than a switch, which wouldn't
+            // compile with aliased values, the first enumerator with the code names it. Zero is
+            // success and never has a name.
+            output += &format!(
+                "static const char* {}_error_name(int32_t code) {{\n",
+                system.ident
+            );
+            output += "    if (code == 0) {\n";
+            output += "        return nullptr;\n";
+            output += "    }\n";
+
+            for (code, name) in names {
+                output += &format!("    if (code == {code}) {{\n");
+                output += &format!("        return \"{name}\";\n");
+                output += "    }\n";
+            }
+
+            output += "\n";
+            output += "    return nullptr;\n";
+            output += "}\n\n";
+        };
+
+        for (index, system) in self.systems.iter().enumerate() {
+            gen_system_fn(index, system);
+        }
+
+        output
+    }
+
+    fn gen_system_descriptors(&self) -> String {
+        let mut output = String::new();
+
+        for system in &self.systems {
+            for (i, input) in system.inputs.iter().enumerate() {
+                let ArgType::Query { inputs } = &input.arg_type else {
+                    continue;
+                };
+
+                output += &format!(
+                    "static const QueryTermDescriptor {}_ARG_{i}_TERMS[] = {{\n",
+                    system.ident
+                );
+
+                for input in inputs {
+                    output += &format!(
+                        "    {{ {}, \"{}\" }},\n",
+                        arg_type_name(input),
+                        self.string_id(&input.ident)
+                    );
+                }
+
+                output += "};\n\n";
+            }
+
+            if system.inputs.is_empty() {
+                continue;
+            }
+
+            output += &format!("static const ArgDescriptor {}_ARGS[] = {{\n", system.ident);
+
+            for (i, input) in system.inputs.iter().enumerate() {
+                if let ArgType::Query { inputs } = &input.arg_type {
+                    output += &format!(
+                        "    {{ ArgTypeQuery, nullptr, ComponentTypeComponent, {}, {}_ARG_{i}_TERMS }},\n",
+                        inputs.len(),
+                        system.ident
+                    );
+                } else {
+                    output += &format!(
+                        "    {{ {}, \"{}\", ComponentTypeResource, 0, nullptr }},\n",
+                        arg_type_name(input),
+                        self.string_id(&input.ident)
+                    );
+                }
+            }
+
+            output += "};\n\n";
+        }
+
+        if self.systems.is_empty() {
+            return output;
+        }
+
+        output += "static const SystemDescriptor SYSTEMS[] = {\n";
+
+        for system in &self.systems {
+            let args = if system.inputs.is_empty() {
+                String::from("nullptr")
+            } else {
+                format!("{}_ARGS", system.ident)
+            };
+
+            let error_name = if system.has_error_names() {
+                format!("{}_error_name", system.ident)
+            } else {
+                String::from("nullptr")
+            };
+
+            output += &format!(
+                "    {{ {0}_ffi, {1}, {2}, {args}, {error_name}, \"{0}\", {3} }},\n",
+                system.ident,
+                system.is_once,
+                system.inputs.len(),
+                gen_source_location(&system.source)
+            );
+        }
+
+        output += "};\n\n";
+
+        output
+    }
+
+    fn gen_module_descriptor(&self) -> String {
+        let mut output = String::new();
+
+        let components = if self.structs.is_empty() {
+            "nullptr"
+        } else {
+            "COMPONENTS"
+        };
+
+        let systems = if self.systems.is_empty() {
+            "nullptr"
+        } else {
+            "SYSTEMS"
+        };
+
+        output += "static const ModuleDescriptor MODULE_DESCRIPTOR = {\n";
+        output += "    ENGINE_VERSION,\n";
+        output += &format!("    {},\n", self.structs.len());
+        output += &format!("    {components},\n");
+        output += &format!("    {},\n", self.systems.len());
+        output += &format!("    {systems},\n");
+        output += "};\n\n";
+
+        output += "extern \"C\" const ModuleDescriptor* arete_module_descriptor() {\n";
+        output += "    return &MODULE_DESCRIPTOR;\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    // old per-field exports, kept as thin wrappers over the descriptor tables while the engine
+    // migrates to `arete_module_descriptor`
+
+    fn gen_system_lookup(&self, ret: &str, name: &str, params: &[&str], body: &[&str]) -> String {
+        let mut lines = Vec::new();
+
+        if self.systems.is_empty() {
+            lines.push(String::from(
+                "return fail(ModuleStatusUnknownSystem, \"unknown system %zu, the module has none\", system_index);",
+            ));
+        } else {
+            lines.push(format!("if (system_index >= {}) {{", self.systems.len()));
+            lines.push(String::from(
+                "    return fail(ModuleStatusUnknownSystem, \"unknown system %zu\", system_index);",
+            ));
+            lines.push(String::from("}"));
+            lines.push(String::new());
+            lines.extend(body.iter().map(|line| line.to_string()));
+            lines.push(String::from("return ModuleStatusOk;"));
+        }
+
+        self.gen_fallible(ret, name, params, &lines)
+    }
+
+    fn gen_systems_len(&self) -> String {
+        let mut output = String::new();
+
+        output += "extern \"C\" size_t systems_len() {\n";
+        output += &format!("    return {};\n", self.systems.len());
+        output += "}\n\n";
+
+        output
+    }
+
+    fn gen_system_is_once(&self) -> String {
+        self.gen_system_lookup(
+            "bool",
+            "system_is_once",
+            &["size_t system_index"],
+            &["*out = SYSTEMS[system_index].is_once;"],
+        )
+    }
+
+    fn gen_system_fn(&self) -> String {
+        self.gen_system_lookup(
+            "system_fn_ptr",
+            "system_fn",
+            &["size_t system_index"],
+            &["*out = SYSTEMS[system_index].fn;"],
+        )
+    }
+
+    fn gen_system_name(&self) -> String {
+        self.gen_system_lookup(
+            "const char*",
+            "system_name",
+            &["size_t system_index"],
+            &["*out = SYSTEMS[system_index].name;"],
+        )
+    }
+
+    fn gen_system_source_location(&self) -> String {
+        self.gen_system_lookup(
+            "SourceLocation",
+            "system_source_location",
+            &["size_t system_index"],
+            &["*out = SYSTEMS[system_index].source;"],
+        )
+    }
+
+    fn gen_system_error_name(&self) -> String {
+        self.gen_system_lookup(
+            "const char*",
+            "system_error_name",
+            &["size_t system_index", "int32_t code"],
+            &[
+                "const SystemDescriptor& system = SYSTEMS[system_index];",
+                "*out = system.error_name != nullptr ? system.error_name(code) : nullptr;",
+            ],
+        )
+    }
+
+    fn gen_system_args_len(&self) -> String {
+        self.gen_system_lookup(
+            "size_t",
+            "system_args_len",
+            &["size_t system_index"],
+            &["*out = SYSTEMS[system_index].args_len;"],
+        )
+    }
+
+    fn gen_system_arg_type(&self) -> String {
+        self.gen_system_lookup(
+            "ArgType",
+            "system_arg_type",
+            &["size_t system_index", "size_t arg_index"],
+            &[
+                "const SystemDescriptor& system = SYSTEMS[system_index];",
+                "if (arg_index >= system.args_len) {",
+                "    return fail(ModuleStatusUnknownArg, \"system %zu has no argument %zu\", system_index, arg_index);",
+                "}",
+                "",
+                "*out = system.args[arg_index].arg_type;",
+            ],
+        )
+    }
+
+    fn gen_system_arg_component(&self) -> String {
+        self.gen_system_lookup(
+            "const char*",
+            "system_arg_component",
+            &["size_t system_index", "size_t arg_index"],
+            &[
+                "const SystemDescriptor& system = SYSTEMS[system_index];",
+                "if (arg_index >= system.args_len) {",
+                "    return fail(ModuleStatusUnknownArg, \"system %zu has no argument %zu\", system_index, arg_index);",
+                "}",
+                "",
+                "if (system.args[arg_index].component == nullptr) {",
+                "    return fail(ModuleStatusWrongArgType, \"argument %zu of system %zu is a query\", arg_index, system_index);",
+                "}",
+                "",
+                "*out = system.args[arg_index].component;",
+            ],
+        )
+    }
+
+    fn gen_system_query_args_len(&self) -> String {
+        self.gen_system_lookup(
+            "size_t",
+            "system_query_args_len",
+            &["size_t system_index", "size_t arg_index"],
+            &[
+                "const SystemDescriptor& system = SYSTEMS[system_index];",
+                "if (arg_index >= system.args_len) {",
+                "    return fail(ModuleStatusUnknownArg, \"system %zu has no argument %zu\", system_index, arg_index);",
+                "}",
+                "",
+                "if (system.args[arg_index].arg_type != ArgTypeQuery) {",
+                "    return fail(ModuleStatusWrongArgType, \"argument %zu of system %zu is not a query\", arg_index, system_index);",
+                "}",
+                "",
+                "*out = system.args[arg_index].query_terms_len;",
+            ],
+        )
+    }
+
+    fn gen_system_query_term(&self, ret: &str, name: &str, field: &str) -> String {
+        self.gen_system_lookup(
+            ret,
+            name,
+            &[
+                "size_t system_index",
+                "size_t arg_index",
+                "size_t query_index",
+            ],
+            &[
+                "const SystemDescriptor& system = SYSTEMS[system_index];",
+                "if (arg_index >= system.args_len) {",
+                "    return fail(ModuleStatusUnknownArg, \"system %zu has no argument %zu\", system_index, arg_index);",
+                "}",
+                "",
+                "const ArgDescriptor& arg = system.args[arg_index];",
+                "if (arg.arg_type != ArgTypeQuery) {",
+                "    return fail(ModuleStatusWrongArgType, \"argument %zu of system %zu is not a query\", arg_index, system_index);",
+                "}",
+                "",
+                "if (query_index >= arg.query_terms_len) {",
+                "    return fail(ModuleStatusUnknownQueryTerm, \"argument %zu of system %zu has no term %zu\", arg_index, system_index, query_index);",
+                "}",
+                "",
+                &format!("*out = arg.query_terms[query_index].{field};"),
+            ],
+        )
+    }
+
+    fn gen_system_query_arg_type(&self) -> String {
+        self.gen_system_query_term("ArgType", "system_query_arg_type", "arg_type")
+    }
+
+    fn gen_system_query_arg_component(&self) -> String {
+        self.gen_system_query_term("const char*", "system_query_arg_component", "component")
+    }
+
+    // Exported twice: `try_<name>` writes any result through `out` and returns a status, leaving a
+    // message for `module_last_error` on failure and clearing it on success, while `<name>` prints
+    // the message and aborts on failure.
+    fn gen_fallible(&self, ret: &str, name: &str, params: &[&str], body: &[String]) -> String {
+        let mut output = String::new();
+
+        let args: Vec<_> = params
+            .iter()
+            .map(|param| param.rsplit(' ').next().unwrap())
+            .collect();
+
+        // nothing to write back for `void`
+        let out = if ret == "void" {
+            String::new()
+        } else {
+            format!(", {ret}* out")
+        };
+
+        output += &format!(
+            "extern \"C\" ModuleStatus try_{name}({}{out}) {{\n",
+            params.join(", ")
+        );
+        output += "    LAST_ERROR[0] = '\\0';\n\n";
+
+        for line in body {
+            if !line.is_empty() {
+                output += "    ";
+                output += line;
+            }
+            output += "\n";
+        }
+
+        output += "}\n\n";
+
+        output += &format!("extern \"C\" {ret} {name}({}) {{\n", params.join(", "));
+        if ret == "void" {
+            output += &format!(
+                "    if (try_{name}({}) != ModuleStatusOk) {{\n",
+                args.join(", ")
+            );
+        } else {
+            output += &format!("    {ret} value {{}};\n");
+            output += &format!(
+                "    if (try_{name}({}, &value) != ModuleStatusOk) {{\n",
+                args.join(", ")
+            );
+        }
+        output += &format!("        std::fprintf(stderr, \"{name}: %s\\n\", LAST_ERROR);\n");
+        output += "        std::abort();\n";
+        output += "    }\n";
+        if ret != "void" {
+            output += "\n";
+            output += "    return value;\n";
+        }
+        output += "}\n\n";
+
+        output
+    }
+
+    // `fail` records a message for the engine to log before returning `status`
+    fn gen_last_error(&self) -> String {
+        let mut output = String::new();
+
+        output += "static thread_local char LAST_ERROR[256];\n\n";
+
+        output += "static ModuleStatus fail(ModuleStatus status, const char* format, ...) {\n";
+        output += "    va_list args;\n";
+        output += "    va_start(args, format);\n";
+        output += "    std::vsnprintf(LAST_ERROR, sizeof(LAST_ERROR), format, args);\n";
+        output += "    va_end(args);\n\n";
+        output += "    return status;\n";
+        output += "}\n\n";
+
+        output += "extern \"C\" const char* module_last_error() {\n";
+        output += "    return LAST_ERROR[0] != '\\0' ? LAST_ERROR : nullptr;\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    fn gen_version(&self) -> String {
+        let mut output = String::new();
+
+        if let Some(engine_version) = self.catalogue.engine_version {
+            output += &format!(
+                "static_assert(ENGINE_VERSION == {engine_version}, \"engine manifest is for a different engine version\");\n"
+            );
+        }
+
+        // fields of engine types are checked too, their layout is part of the component's
+        let field_types = self
+            .structs
+            .iter()
+            .flat_map(|s| &s.fields)
+            .filter_map(|field| self.catalogue.find(&field.ty));
+
+        let mut used: Vec<_> = self
+            .public_inputs()
+            .into_iter()
+            .chain(field_types)
+            .collect();
+        used.sort_unstable_by(|a, b| a.string_id.cmp(&b.string_id));
+        used.dedup_by(|a, b| a.string_id == b.string_id);
+
+        for public in used {
+            if let Some(size) = public.size {
+                output += &format!(
+                    "static_assert(sizeof({0}) == {size}, \"size of {0} does not match the engine manifest\");\n",
+                    public.ident
+                );
+            }
+
+            if let Some(align) = public.align {
+                output += &format!(
+                    "static_assert(alignof({0}) == {align}, \"alignment of {0} does not match the engine manifest\");\n",
+                    public.ident
+                );
+            }
+        }
+
+        if !output.is_empty() {
+            output += "\n";
+        }
+
+        output += "extern \"C\" uint32_t arete_target_version() {\n";
+        output += "    return ENGINE_VERSION;\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    fn gen_callbacks(&self, options: &CppOptions) -> String {
+        let mut output = String::new();
+
+        for callback in CALLBACKS {
+            output += &format!(
+                "{} (*{})({});\n",
+                callback.ret, callback.ident, callback.args
+            );
+        }
+        output += "\n";
+
+        // only the exception guards around systems and resource constructors report errors
+        let has_guards = !self.systems.is_empty()
+            || self
+                .structs
+                .iter()
+                .any(|s| s.struct_type == StructType::Resource);
+
+        if !options.no_exceptions && has_guards {
+            output += "static void report_error(const char* context, const char* message) {\n";
+            output += "    if (ReportErrorFn != nullptr) {\n";
+            output += "        ReportErrorFn(context, message);\n";
+            output += "    }\n";
+            output += "}\n\n";
+        }
+
+        // ends the profile scope however the system exits
+        if options.profile {
+            output += "struct ProfileScope {\n";
+            output += "    size_t system_index;\n\n";
+            output +=
+                "    explicit ProfileScope(size_t system_index) : system_index(system_index) {\n";
+            output += "        if (ProfileBeginFn != nullptr) {\n";
+            output += "            ProfileBeginFn(system_index);\n";
+            output += "        }\n";
+            output += "    }\n\n";
+            output += "    ~ProfileScope() {\n";
+            output += "        if (ProfileEndFn != nullptr) {\n";
+            output += "            ProfileEndFn(system_index);\n";
+            output += "        }\n";
+            output += "    }\n";
+            output += "};\n\n";
+        }
+
+        output += &self.gen_module_init();
+
+        output
+    }
+
+    // An engine built against an older layout passes a smaller `size`, leaving the callbacks it
+    // doesn't know about null. Fails without touching the callbacks if any the module needs are
+    // missing.
+    fn gen_module_init(&self) -> String {
+        let mut output = String::new();
+
+        let has_queries = self.systems.iter().any(|system| {
+            system
+                .inputs
+                .iter()
+                .any(|input| matches!(input.arg_type, ArgType::Query { .. }))
+        });
+
+        let required: Vec<_> = CALLBACKS
+            .iter()
+            .filter(|callback| callback.required && has_queries)
+            .collect();
+
+        output += "extern \"C\" ModuleStatus module_init(const EngineCallbacks* callbacks) {\n";
+        output += "    if (callbacks == nullptr ||\n";
+        output += &format!(
+            "        callbacks->size < offsetof(EngineCallbacks, {}) ||\n",
+            CALLBACKS[0].field
+        );
+        output += "        callbacks->version != ENGINE_CALLBACKS_VERSION) {\n";
+        output += "        return fail(\n";
+        output += "            ModuleStatusUnsupportedCallbacks,\n";
+        output += "            \"unsupported engine callbacks, expected version %d\",\n";
+        output += "            ENGINE_CALLBACKS_VERSION\n";
+        output += "        );\n";
+        output += "    }\n\n";
+
+        output += "    EngineCallbacks provided = {};\n";
+        output += "    std::memcpy(\n";
+        output += "        &provided,\n";
+        output += "        callbacks,\n";
+        output += "        callbacks->size < sizeof(EngineCallbacks) ? callbacks->size : sizeof(EngineCallbacks)\n";
+        output += "    );\n\n";
+
+        if !required.is_empty() {
+            let names: usize = required.iter().map(|c| c.field.len() + 1).sum();
+
+            output += &format!("    char missing[{}] = \"\";\n", names + 1);
+            for callback in &required {
+                output += &format!("    if (provided.{} == nullptr) {{\n", callback.field);
+                output += &format!("        std::strcat(missing, \" {}\");\n", callback.field);
+                output += "    }\n";
+            }
+            output += "\n";
+            output += "    if (missing[0] != '\\0') {\n";
+            output += "        return fail(ModuleStatusMissingCallback, \"the engine doesn't provide%s\", missing);\n";
+            output += "    }\n\n";
+        }
+
+        for callback in CALLBACKS {
+            output += &format!("    {} = provided.{};\n", callback.ident, callback.field);
+        }
+        output += "\n";
+
+        output += "    return ModuleStatusOk;\n";
+        output += "}\n\n";
+
+        output
+    }
+
+    // The engine rebuilds this description from the descriptor it reads plus its own callback
+    // signatures, through `expected_abi_hash` in the Rust bindings, so any disagreement about the
+    // model changes the hash.
+    fn abi_description(&self) -> String {
+        let mut output = String::new();
+
+        // the same access the descriptor reports
+        let access = |input: &SystemInputInfo| match arg_type_name(input) {
+            "ArgTypeQuery" => "query",
+            "ArgTypeDataAccessMut" => "mut",
+            _ => "ref",
+        };
+
+        for struct_info in &self.structs {
+            let kind = match struct_info.struct_type {
+                StructType::Component => "component",
+                StructType::Resource => "resource",
+            };
+            output += &format!("component {} {kind}\n", struct_info.string_id);
+        }
+
+        for system in &self.systems {
+            output += if system.is_once {
+                "system once\n"
+            } else {
+                "system always\n"
+            };
+
+            for input in &system.inputs {
+                if let ArgType::Query { inputs } = &input.arg_type {
+                    output += "arg query\n";
+                    for input in inputs {
+                        output +=
+                            &format!("term {} {}\n", access(input), self.string_id(&input.ident));
+                    }
+                } else {
+                    output += &format!("arg {} {}\n", access(input), self.string_id(&input.ident));
+                }
+            }
+        }
+
+        output += &callback_description();
+
+        output
+    }
+
+    // What `arete_abi_hash` returns for this module, only known up front when the catalogue gives
+    // the layout of every engine type the systems take.
+    pub(crate) fn expected_abi_hash(&self) -> Option<u64> {
+        let layouts = self
+            .public_inputs()
+            .iter()
+            .map(|public| public.size.zip(public.align))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(abi_hash_layouts(
+            abi_hash(self.abi_description().as_bytes()),
+            &layouts,
+        ))
+    }
+
+    // The model hash is computed here, the layouts of the engine types the systems take are mixed
+    // in by the C++ compiler so that a layout change without an engine version bump is still
+    // caught.
+    fn gen_abi_hash(&self) -> String {
+        let mut output = String::new();
+
+        output += "static constexpr uint64_t compute_abi_hash() {\n";
+        output += &format!(
+            "    uint64_t hash = 0x{:016x}ull;\n\n",
+            abi_hash(self.abi_description().as_bytes())
+        );
+
+        let public = self.public_inputs();
+        if !public.is_empty() {
+            output += "    const uint64_t layouts[] = {\n";
+
+            for public in public {
+                output += &format!("        sizeof({}),\n", public.ident);
+                output += &format!("        alignof({}),\n", public.ident);
+            }
+
+            output += "    };\n\n";
+            output += "    for (uint64_t value : layouts) {\n";
+            output += "        for (int i = 0; i < 8; ++i) {\n";
+            output += "            hash ^= (value >> (i * 8)) & 0xff;\n";
+            output += "            hash *= 0x100000001b3ull;\n";
+            output += "        }\n";
+            output += "    }\n\n";
+        }
+
+        output += "    return hash;\n";
+        output += "}\n\n";
+
+        output += "extern \"C\" uint64_t arete_abi_hash() {\n";
+        output += "    constexpr uint64_t hash = compute_abi_hash();\n";
+        output += "    return hash;\n";
+        output += "}\n\n";
+
+        output
+    }
+}
+
+// the engine's side of the description, which doesn't depend on the module
+pub(crate) fn callback_description() -> String {
+    let mut output = String::new();
+
+    for callback in CALLBACKS {
+        output += &format!(
+            "callback {} {}({})\n",
+            callback.ident, callback.ret, callback.args
+        );
+    }
+
+    output
+}
+
+// the file is written as the generator was given it, escaped for a C string literal
+fn gen_source_location(source: &SourceLocation) -> String {
+    let file = source.file.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{{ \"{file}\", {} }}", source.line)
+}
+
+// Exceptions must not unwind into the engine, so they are reported and turned into error code
+// -1 at the boundary. `body` is indented for a function body and must return on every path.
+fn gen_exception_guard(options: &CppOptions, context: &str, body: String) -> String {
+    if options.no_exceptions {
+        return body;
+    }
+
+    let mut output = String::new();
+
+    output += "    try {\n";
+    for line in body.lines() {
+        if !line.is_empty() {
+            output += "    ";
+            output += line;
+        }
+        output += "\n";
+    }
+    output += "    } catch (const std::exception& e) {\n";
+    output += &format!("        report_error({context}, e.what());\n");
+    output += "        return -1;\n";
+    output += "    } catch (...) {\n";
+    output += &format!("        report_error({context}, \"unknown exception\");\n");
+    output += "        return -1;\n";
+    output += "    }\n";
+
+    output
+}
@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+use std::{fmt, io};
+
+use crate::SourceLocation;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    // a declaration the parser can't make sense of
+    Parse {
+        source: SourceLocation,
+        message: String,
+    },
+    // the module parsed, but can't be exposed to the engine as written
+    Invalid {
+        source: SourceLocation,
+        message: String,
+    },
+}
+
+impl Error {
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub(crate) fn json(path: &Path, source: serde_json::Error) -> Self {
+        Self::Json {
+            path: path.to_owned(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Json { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Parse { source, message } | Self::Invalid { source, message } => {
+                write!(f, "{}:{}: {message}", source.file, source.line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Json { source, .. } => Some(source),
+            Self::Parse { .. } | Self::Invalid { .. } => None,
+        }
+    }
+}
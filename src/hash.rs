@@ -0,0 +1,234 @@
+// 64-bit FNV-1a, continued over the public component layouts by `compute_abi_hash`
+pub(crate) fn abi_hash(bytes: &[u8]) -> u64 {
+    fnv1a(0xcbf29ce484222325, bytes)
+}
+
+// the size and alignment of each public component, mixed in as `compute_abi_hash` does
+pub(crate) fn abi_hash_layouts(hash: u64, layouts: &[(usize, usize)]) -> u64 {
+    layouts
+        .iter()
+        .flat_map(|&(size, align)| [size as u64, align as u64])
+        .fold(hash, |hash, value| fnv1a(hash, &value.to_le_bytes()))
+}
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+// Generates a minimal perfect hash over `string_ids` as static tables, plus a `fn_name` function
+// mapping a string id to its position in `string_ids`, or -1 if it is unknown. A lookup costs at
+// most two hashes and one string comparison, no matter how many string ids there are.
+pub(crate) fn gen_string_id_lookup(
+    fn_name: &str,
+    table_prefix: &str,
+    string_ids: &[&str],
+    string_id_at_index: &str,
+) -> String {
+    let mut output = String::new();
+
+    output += &format!("static int32_t {fn_name}(const char* string_id) {{\n");
+
+    if string_ids.is_empty() {
+        output += "    return -1;\n";
+        output += "}\n\n";
+        return output;
+    }
+
+    let hash = StringIdHash::new(string_ids);
+    let len = string_ids.len();
+
+    let mut tables = String::new();
+
+    tables += &format!("static const int32_t {table_prefix}_HASH_DISPLACEMENTS[] = {{");
+    for (i, displacement) in hash.displacements.iter().enumerate() {
+        tables += if i % 8 == 0 { "\n    " } else { " " };
+        tables += &format!("{displacement},");
+    }
+    tables += "\n};\n\n";
+
+    tables += &format!("static const int32_t {table_prefix}_HASH_SLOTS[] = {{");
+    for (i, index) in hash.slots.iter().enumerate() {
+        tables += if i % 8 == 0 { "\n    " } else { " " };
+        tables += &format!("{index},");
+    }
+    tables += "\n};\n\n";
+
+    output = tables + &output;
+
+    output += &format!(
+        "    int32_t displacement = {table_prefix}_HASH_DISPLACEMENTS[string_id_hash(string_id, 0) % {len}];\n"
+    );
+    output += "    uint32_t slot = displacement < 0\n";
+    output += "        ? static_cast<uint32_t>(-displacement - 1)\n";
+    output += &format!("        : string_id_hash(string_id, displacement) % {len};\n");
+    output += &format!("    int32_t index = {table_prefix}_HASH_SLOTS[slot];\n\n");
+    output += &format!("    if (std::strcmp(string_id, {string_id_at_index}) != 0) {{\n");
+    output += "        return -1;\n";
+    output += "    }\n\n";
+    output += "    return index;\n";
+    output += "}\n\n";
+
+    output
+}
+
+pub(crate) fn gen_string_id_hash() -> String {
+    let mut output = String::new();
+
+    output += "static uint32_t string_id_hash(const char* string_id, uint32_t seed) {\n";
+    output += "    uint32_t hash = 2166136261u ^ seed;\n";
+    output += "    for (; *string_id != '\\0'; ++string_id) {\n";
+    output += "        hash ^= static_cast<uint8_t>(*string_id);\n";
+    output += "        hash *= 16777619u;\n";
+    output += "    }\n\n";
+    output += "    hash ^= hash >> 16;\n";
+    output += "    hash *= 0x85ebca6bu;\n";
+    output += "    hash ^= hash >> 13;\n";
+    output += "    hash *= 0xc2b2ae35u;\n";
+    output += "    hash ^= hash >> 16;\n\n";
+    output += "    return hash;\n";
+    output += "}\n\n";
+
+    output
+}
+
+// must match the `string_id_hash` emitted by `gen_string_id_hash`
+fn string_id_hash(string_id: &str, seed: u32) -> u32 {
+    let mut hash = 2166136261u32 ^ seed;
+    for b in string_id.bytes() {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(16777619);
+    }
+
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+
+    hash
+}
+
+// Hash and displace: string ids are grouped into buckets by their unseeded hash, then each bucket
+// searches for a seed which moves all of its string ids into free slots. Buckets holding a single
+// string id skip the search and store the free slot directly, encoded as a negative displacement.
+struct StringIdHash {
+    displacements: Vec<i32>,
+    slots: Vec<usize>,
+}
+
+impl StringIdHash {
+    fn new(string_ids: &[&str]) -> Self {
+        let len = string_ids.len();
+
+        let mut buckets = vec![Vec::new(); len];
+        for (i, string_id) in string_ids.iter().enumerate() {
+            let bucket = &mut buckets[string_id_hash(string_id, 0) as usize % len];
+            assert!(
+                bucket.iter().all(|&j| string_ids[j] != *string_id),
+                "duplicate string id {string_id:?}"
+            );
+            bucket.push(i);
+        }
+
+        let mut bucket_order: Vec<_> = (0..len).collect();
+        bucket_order.sort_by_key(|&b| std::cmp::Reverse(buckets[b].len()));
+
+        let mut displacements = vec![0i32; len];
+        let mut slots: Vec<Option<usize>> = vec![None; len];
+
+        let mut bucket_order = bucket_order.into_iter().peekable();
+
+        while let Some(&b) = bucket_order.peek() {
+            if buckets[b].len() <= 1 {
+                break;
+            }
+
+            bucket_order.next();
+
+            'seeds: for seed in 1.. {
+                let mut bucket_slots = Vec::new();
+
+                for &i in &buckets[b] {
+                    let slot = string_id_hash(string_ids[i], seed) as usize % len;
+                    if slots[slot].is_some() || bucket_slots.contains(&slot) {
+                        continue 'seeds;
+                    }
+                    bucket_slots.push(slot);
+                }
+
+                for (&i, slot) in buckets[b].iter().zip(bucket_slots) {
+                    slots[slot] = Some(i);
+                }
+
+                displacements[b] = seed as i32;
+                break;
+            }
+        }
+
+        let mut free_slots = (0..len)
+            .filter(|&slot| slots[slot].is_none())
+            .collect::<Vec<_>>();
+
+        for b in bucket_order.filter(|&b| buckets[b].len() == 1) {
+            let slot = free_slots.pop().unwrap();
+            slots[slot] = Some(buckets[b][0]);
+            displacements[b] = -(slot as i32) - 1;
+        }
+
+        Self {
+            displacements,
+            slots: slots.into_iter().map(Option::unwrap).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // mirrors the `*_index` functions emitted by `gen_string_id_lookup`, counting hashes
+    fn lookup(hash: &StringIdHash, string_ids: &[String], string_id: &str) -> (Option<usize>, u32) {
+        let len = string_ids.len();
+        let mut hashes = 1;
+
+        let displacement = hash.displacements[string_id_hash(string_id, 0) as usize % len];
+        let slot = if displacement < 0 {
+            (-displacement - 1) as usize
+        } else {
+            hashes += 1;
+            string_id_hash(string_id, displacement as u32) as usize % len
+        };
+
+        let index = hash.slots[slot];
+        ((string_ids[index] == string_id).then_some(index), hashes)
+    }
+
+    #[test]
+    fn string_id_lookup_cost_is_flat() {
+        for len in [1, 10, 100, 1_000, 10_000] {
+            let string_ids: Vec<_> = (0..len)
+                .map(|i| format!("game_module::Component{i}"))
+                .collect();
+            let refs: Vec<_> = string_ids.iter().map(String::as_str).collect();
+
+            let hash = StringIdHash::new(&refs);
+
+            // one hash into the displacements and at most one more into the slots, however many
+            // string ids there are
+            for (i, string_id) in string_ids.iter().enumerate() {
+                let (index, hashes) = lookup(&hash, &string_ids, string_id);
+                assert_eq!(index, Some(i));
+                assert!(hashes <= 2, "{string_id} took {hashes} hashes among {len}");
+            }
+
+            let (index, hashes) = lookup(&hash, &string_ids, "game_module::Unknown");
+            assert_eq!(index, None);
+            assert!(hashes <= 2);
+        }
+    }
+}
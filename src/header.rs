@@ -0,0 +1,49 @@
+use crate::abi::{gen_types, ENGINE_TYPES};
+use crate::FfiGenerator;
+
+impl FfiGenerator {
+    // The declarations are taken from the generated implementation itself, so the header can't
+    // disagree with what the module actually exports.
+    pub(crate) fn gen_header(&self) -> String {
+        let mut output = String::new();
+
+        output += "#ifndef ARETE_MODULE_FFI_H\n";
+        output += "#define ARETE_MODULE_FFI_H\n\n";
+
+        output += "#include <stdbool.h>\n";
+        output += "#include <stddef.h>\n";
+        output += "#include <stdint.h>\n\n";
+
+        output += "#ifndef ARETE_ENGINE_TYPES\n";
+        output += "#define ARETE_ENGINE_TYPES\n";
+        for abi_type in ENGINE_TYPES {
+            output += &abi_type.gen_c();
+        }
+        output += "#endif\n\n";
+
+        output += "#ifdef __cplusplus\n";
+        output += "extern \"C\" {\n";
+        output += "#endif\n\n";
+
+        output += &gen_types();
+
+        if let Some(hash) = self.expected_abi_hash() {
+            output += "// what `arete_abi_hash` returns when the module matches this header\n";
+            output += &format!("#define ARETE_ABI_HASH 0x{hash:016x}ull\n\n");
+        }
+
+        for export in self.exports() {
+            // an empty parameter list doesn't declare a prototype in C
+            output += &export.replace("()", "(void)");
+            output += ";\n\n";
+        }
+
+        output += "#ifdef __cplusplus\n";
+        output += "}\n";
+        output += "#endif\n\n";
+
+        output += "#endif\n";
+
+        output
+    }
+}
@@ -0,0 +1,128 @@
+//! Generates the glue between an Arete game module and the engine.
+//!
+//! A module header is parsed into an [`FfiGenerator`] and emitted through one of the [`Backend`]s,
+//! after `generate` has validated it:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use arete_codegen::{Backend, CppOptions, EngineCatalogue};
+//!
+//! let module = arete_codegen::parse_file(Path::new("game.h"), EngineCatalogue::default())?;
+//!
+//! let ffi = module.generate(&Backend::Cpp {
+//!     include: String::from("game.h"),
+//!     options: CppOptions::default(),
+//! })?;
+//! arete_codegen::write_if_changed(Path::new("ffi.cpp"), &ffi)?;
+//! # Ok::<(), arete_codegen::Error>(())
+//! ```
+
+mod abi;
+mod cpp;
+mod error;
+mod harness;
+mod hash;
+mod header;
+mod manifest;
+mod model;
+mod parse;
+mod queries;
+mod rust;
+
+use std::fs;
+use std::path::Path;
+
+pub use error::Error;
+pub use manifest::{
+    diff_manifests, Access, Change, ManifestField, ManifestParam, ManifestStruct, ManifestSystem,
+    ManifestTerm, ModuleManifest, Schedule,
+};
+pub use model::{
+    ArgType, EngineCatalogue, FfiGenerator, FieldInfo, PublicStructInfo, ReturnType,
+    SourceLocation, StructInfo, StructType, SystemInfo, SystemInputInfo,
+};
+pub use parse::{parse_file, parse_source};
+
+#[derive(Debug, Clone)]
+pub enum Backend {
+    // ffi.cpp, compiled into the module next to the header given by `include`
+    Cpp {
+        include: String,
+        options: CppOptions,
+    },
+    // ffi.h, declaring the module ABI for C callers
+    CHeader,
+    // a `ModuleApi` for loading the module from Rust
+    RustBindings,
+    // the module manifest as JSON
+    Manifest,
+    // header-only fake world for unit testing systems, next to `include` and the C `header`
+    TestHarness {
+        include: String,
+        header: String,
+    },
+    // typed wrappers around the `Query` arguments of the module's systems, next to `include`
+    Queries {
+        include: String,
+    },
+}
+
+// Only change the generated ffi.cpp; the ABI the other backends describe is the same either way.
+#[derive(Debug, Clone, Default)]
+pub struct CppOptions {
+    // adds an `arete_module_manifest` export returning the module manifest
+    pub embed_manifest: bool,
+    // leaves out the exception guards, for modules built with `-fno-exceptions`
+    pub no_exceptions: bool,
+    // calls the engine's profiling callbacks around every system
+    pub profile: bool,
+}
+
+impl FfiGenerator {
+    pub fn generate(&self, backend: &Backend) -> Result<String, Error> {
+        // the generators look up string ids and names that only validation guarantees
+        self.validate()?;
+
+        let output = match backend {
+            Backend::Cpp { include, options } => {
+                let mut header = String::new();
+
+                // everything the generated code uses is included here, rather than relying on the
+                // module or engine headers to pull it in
+                header += &format!("#include {include:?}\n");
+                header += "#include <cstdarg>\n";
+                header += "#include <cstddef>\n";
+                header += "#include <cstdint>\n";
+                header += "#include <cstdio>\n";
+                header += "#include <cstdlib>\n";
+                header += "#include <cstring>\n";
+                if !options.no_exceptions {
+                    header += "#include <exception>\n";
+                }
+                header += "#include <memory>\n";
+                header += "#include <type_traits>\n\n";
+
+                self.gen_ffi(header, options)
+            }
+            Backend::CHeader => self.gen_header(),
+            Backend::RustBindings => self.gen_rust_bindings(),
+            Backend::Manifest => serde_json::to_string_pretty(&self.manifest()).unwrap(),
+            Backend::TestHarness { include, header } => self.gen_test_harness(include, header),
+            Backend::Queries { include } => self.gen_queries(include),
+        };
+
+        Ok(output)
+    }
+}
+
+// Files that already hold `contents` are left alone, so their timestamps don't trigger rebuilds.
+// Returns whether the file was written.
+pub fn write_if_changed(path: &Path, contents: &str) -> Result<bool, Error> {
+    if fs::read_to_string(path).ok().as_deref() == Some(contents) {
+        return Ok(false);
+    }
+
+    fs::write(path, contents).map_err(|err| Error::io(path, err))?;
+    Ok(true)
+}
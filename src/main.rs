@@ -1,899 +1,298 @@
-use ::std::{env, fs};
-use std::path::Path;
-
-use clap::Parser;
-use regex::Regex;
-
-const ARETE_PUBLIC_COMPONENTS: &[&str] = &[
-    "Camera",
-    "Color",
-    "DirectionalLight",
-    "DynamicStaticMesh",
-    "PointLight",
-    "Transform",
-];
-
-#[derive(Debug)]
-pub enum StructType {
-    Component,
-    Resource,
-}
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
 
-#[derive(Debug)]
-pub enum ArgType {
-    DataAccessDirect,
-    DataAccessCell,
-    Query { inputs: Vec<SystemInputInfo> },
-}
-
-#[derive(Debug)]
-pub struct SystemInfo {
-    pub ident: String,
-    pub is_once: bool,
-    pub inputs: Vec<SystemInputInfo>,
-}
-
-#[derive(Debug)]
-pub struct SystemInputInfo {
-    pub ident: String,
-    pub arg_type: ArgType,
-    pub mutable: bool,
-}
-
-#[derive(Debug)]
-pub struct StructInfo {
-    pub ident: String,
-    pub string_id: String,
-    pub struct_type: StructType,
-}
+use arete_codegen::{Backend, CppOptions, EngineCatalogue, Error, FfiGenerator, ModuleManifest};
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
-#[command(author, version)]
+#[command(
+    author,
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    #[arg(short, long)]
-    input: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(short, long, required = true)]
+    input: Option<String>,
 
     #[arg(short, long)]
     output: Option<String>,
-}
-
-fn main() {
-    let args = Args::parse();
 
-    let current_exe = env::current_exe().unwrap();
-    let exe_dir = current_exe.parent().unwrap();
+    /// C header declaring the module ABI, only written when given
+    #[arg(long)]
+    header: Option<String>,
 
-    let input = exe_dir.join(&args.input);
+    /// Rust bindings for loading the module from the engine, only written when given
+    #[arg(long)]
+    rust_bindings: Option<String>,
 
-    let output = if let Some(output) = args.output {
-        exe_dir.join(output)
-    } else {
-        exe_dir.join(&args.input).parent().unwrap().join("ffi.cpp")
-    };
+    /// C++ header with typed queries for the module's systems, only written when given
+    #[arg(long)]
+    queries: Option<String>,
 
-    let file = filter_read_file(&input);
+    /// Header-only fake world for unit testing systems in C++, only written when given
+    #[arg(long, requires = "header")]
+    test_harness: Option<String>,
 
-    let mut ffi_generator = FfiGenerator::default();
-
-    let re = Regex::new("COMPONENT\\(\\s*(\\S+)\\s*\\)").unwrap();
-    for component in re.captures_iter(&file) {
-        parse_struct(
-            &mut ffi_generator,
-            component.get(1).unwrap().as_str(),
-            false,
-        );
-    }
+    /// Public component and resource catalogue shipped with the engine, replacing the built-in
+    /// list, which only has components
+    #[arg(short, long)]
+    engine_manifest: Option<String>,
 
-    let re = Regex::new("RESOURCE\\(\\s*(\\S+)\\s*\\)").unwrap();
-    for resource in re.captures_iter(&file) {
-        parse_struct(&mut ffi_generator, resource.get(1).unwrap().as_str(), true);
-    }
+    /// JSON description of the module's components, resources and systems
+    #[arg(long)]
+    manifest: Option<String>,
 
-    let re = Regex::new("SYSTEM_ONCE\\([\\s\\S]*?(\\S+)\\s*,([\\s\\S]+?)\\)").unwrap();
-    for system in re.captures_iter(&file) {
-        parse_system(
-            &mut ffi_generator,
-            system.get(1).unwrap().as_str(),
-            system.get(2).unwrap().as_str(),
-            true,
-        );
-    }
+    /// Embed the module manifest behind an `arete_module_manifest` export
+    #[arg(long)]
+    embed_manifest: bool,
 
-    let re = Regex::new("SYSTEM\\([\\s\\S]*?(\\S+)\\s*,([\\s\\S]+?)\\)").unwrap();
-    for system in re.captures_iter(&file) {
-        parse_system(
-            &mut ffi_generator,
-            system.get(1).unwrap().as_str(),
-            system.get(2).unwrap().as_str(),
-            false,
-        );
-    }
+    /// Leave out the exception guards around systems, for modules built with `-fno-exceptions`
+    #[arg(long)]
+    no_exceptions: bool,
 
-    let mut output_header = String::new();
+    /// Call the engine's profiling callbacks around every system
+    #[arg(long)]
+    profile: bool,
 
-    output_header += &format!("#include {:?}\n", input.file_name().unwrap());
-    output_header += "#include <cstring>\n\n";
+    /// Makefile-style depfile listing every file the outputs were generated from
+    #[arg(long)]
+    depfile: Option<String>,
 
-    fs::write(output, ffi_generator.gen_ffi(output_header)).unwrap();
+    /// Write nothing, and fail if any output is missing or out of date
+    #[arg(long)]
+    check: bool,
 }
 
-fn filter_read_file(path: &Path) -> String {
-    let mut file = String::new();
-
-    for line in fs::read_to_string(path).unwrap().lines() {
-        file += line.find("//").map(|i| &line[..i]).unwrap_or(line);
-    }
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two versions of a module, given as manifests, headers or source directories. Exits
+    /// with 2 on breaking changes, and 1 if either version can't be loaded
+    Diff {
+        old: String,
+        new: String,
+
+        /// Public component catalogue used when parsing headers
+        #[arg(short, long)]
+        engine_manifest: Option<String>,
+    },
+}
 
-    while let Some(i_start) = file.find("/*") {
-        let Some(i_end) = file[i_start..].find("*/") else {
-            // ??
-            break;
-        };
+fn main() {
+    let args = Args::parse();
 
-        file.replace_range(i_start..=i_start + i_end + 1, "");
+    if let Err(err) = run(args) {
+        eprintln!("error: {err}");
+        process::exit(1);
     }
-
-    file
 }
 
-fn parse_system(info: &mut FfiGenerator, ident: &str, mut body: &str, is_once: bool) {
-    let mut inputs = Vec::new();
+fn run(args: Args) -> Result<(), Error> {
+    if let Some(Command::Diff {
+        old,
+        new,
+        engine_manifest,
+    }) = args.command
+    {
+        let catalogue = match engine_manifest {
+            Some(path) => EngineCatalogue::load(Path::new(&path))?,
+            None => EngineCatalogue::default(),
+        };
 
-    body = body.trim();
+        let old = load_manifest(Path::new(&old), &catalogue)?;
+        let new = load_manifest(Path::new(&new), &catalogue)?;
 
-    while !body.is_empty() {
-        let mutable;
-        if body.starts_with("const") {
-            mutable = false;
-            body = body[5..].trim_start();
-        } else {
-            mutable = true;
+        let changes = arete_codegen::diff_manifests(&old, &new);
+        for change in &changes {
+            println!("{change}");
         }
 
-        let ident_end = body
-            .find('&')
-            .expect("all parameters must be taken as references");
-
-        if body.starts_with("Query") {
-            body = body[5..].trim_start();
-            assert!(body.starts_with('<'), "malformed query");
-            body = body[1..].trim_start();
-
-            let mut query_inputs = Vec::new();
-
-            loop {
-                let mutable;
-                if body.starts_with("const") {
-                    mutable = false;
-                    body = body[5..].trim_start();
-                } else {
-                    mutable = true;
-                }
-
-                let ident_end = body.find('&').expect("malformed query");
-
-                let ident = body[..ident_end].trim_end().to_owned();
-
-                query_inputs.push(SystemInputInfo {
-                    ident,
-                    arg_type: ArgType::DataAccessDirect,
-                    mutable,
-                });
-
-                body = body[ident_end + 1..].trim_start();
-
-                if body.starts_with('>') {
-                    break;
-                } else {
-                    body = body[1..].trim_start();
-                }
-            }
-
-            body = body[1..].trim_start();
-
-            inputs.push(SystemInputInfo {
-                ident: "query".to_owned() + &inputs.len().to_string(),
-                arg_type: ArgType::Query {
-                    inputs: query_inputs,
-                },
-                mutable,
-            });
-        } else {
-            let ident = body[..ident_end].trim_end().to_owned();
-
-            inputs.push(SystemInputInfo {
-                ident,
-                arg_type: ArgType::DataAccessDirect,
-                mutable,
-            });
+        // errors exit with 1, so CI can tell them apart from breaking changes
+        if changes.iter().any(|change| change.breaking) {
+            process::exit(2);
         }
 
-        if let Some(i) = body.find(',') {
-            body = body[i + 1..].trim_start();
-        } else {
-            break;
-        }
+        return Ok(());
     }
 
-    info.systems.push(SystemInfo {
-        ident: ident.to_owned(),
-        is_once,
-        inputs,
-    });
-}
+    // clap only lets the input be missing when a subcommand is given
+    let input = PathBuf::from(args.input.unwrap());
 
-fn parse_struct(info: &mut FfiGenerator, ident: &str, is_resource: bool) {
-    let struct_type = if is_resource {
-        StructType::Resource
+    let output = if let Some(output) = args.output {
+        PathBuf::from(output)
     } else {
-        StructType::Component
+        input.parent().unwrap().join("ffi.cpp")
     };
 
-    info.structs.push(StructInfo {
-        ident: ident.to_string(),
-        string_id: String::from("game_module::") + ident,
-        struct_type,
-    });
-}
-
-#[derive(Debug, Default)]
-pub struct FfiGenerator {
-    pub systems: Vec<SystemInfo>,
-    pub structs: Vec<StructInfo>,
-}
-
-impl FfiGenerator {
-    pub fn gen_ffi(self, header: String) -> String {
-        let mut output = header;
-
-        output += &gen_version();
-        output += &self.gen_components();
-        output += &self.gen_resource_init();
-        output += &self.gen_systems();
-        output += &self.gen_callbacks();
-
-        output
-    }
+    let header = args.header.map(PathBuf::from);
 
-    fn gen_components(&self) -> String {
-        let mut output = String::new();
+    let catalogue = match &args.engine_manifest {
+        Some(path) => EngineCatalogue::load(Path::new(path))?,
+        None => EngineCatalogue::default(),
+    };
 
-        output += "enum ComponentType {\n";
-        output += "    ComponentTypeComponent,\n";
-        output += "    ComponentTypeResource,\n";
-        output += "};\n\n";
+    let ffi_generator = arete_codegen::parse_file(&input, catalogue)?;
 
-        output += "enum ArgType {\n";
-        output += "    ArgTypeDataAccessMut,\n";
-        output += "    ArgTypeDataAccessRef,\n";
-        output += "    ArgTypeQuery,\n";
-        output += "};\n\n";
+    let include = input.file_name().unwrap().to_string_lossy().into_owned();
 
-        output += &self.gen_component_string_id();
-        output += &self.gen_component_size();
-        output += &self.gen_component_align();
-        output += &self.gen_component_type();
-        output += &self.gen_set_component_ids();
+    let mut outputs = vec![(
+        output.clone(),
+        ffi_generator.generate(&Backend::Cpp {
+            include: include.clone(),
+            options: CppOptions {
+                embed_manifest: args.embed_manifest,
+                no_exceptions: args.no_exceptions,
+                profile: args.profile,
+            },
+        })?,
+    )];
 
-        output
+    if let Some(header) = &header {
+        outputs.push((header.clone(), ffi_generator.generate(&Backend::CHeader)?));
     }
 
-    fn gen_component_string_id(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" const char* component_string_id(size_t index) {\n";
-        output += "    switch(index) {\n";
-
-        for (i, struct_info) in self.structs.iter().enumerate() {
-            output += &format!("        case {i}: return \"{}\";\n", struct_info.string_id);
-        }
-
-        output += "        default: return nullptr;\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
+    if let Some(manifest) = args.manifest {
+        outputs.push((
+            PathBuf::from(manifest),
+            ffi_generator.generate(&Backend::Manifest)?,
+        ));
     }
 
-    fn gen_component_size(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" size_t component_size(const char* string_id) {\n";
-
-        if self.structs.is_empty() {
-            output += "    std::abort();\n"
-        } else {
-            output += &format!(
-                "    if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                self.structs[0].string_id
-            );
-            output += &format!("        return sizeof({});\n", self.structs[0].ident);
-            for struct_info in &self.structs[1..] {
-                output += &format!(
-                    "    }} else if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                    struct_info.string_id
-                );
-                output += &format!("        return sizeof({});\n", struct_info.ident);
-            }
-
-            output += "    } else {\n";
-            output += "        std::abort();\n";
-            output += "    }\n";
-        }
-
-        output += "}\n\n";
-
-        output
+    if let Some(rust_bindings) = args.rust_bindings {
+        outputs.push((
+            PathBuf::from(rust_bindings),
+            ffi_generator.generate(&Backend::RustBindings)?,
+        ));
     }
 
-    fn gen_component_align(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" size_t component_align(const char* string_id) {\n";
-
-        if self.structs.is_empty() {
-            output += "    std::abort();\n";
-        } else {
-            output += &format!(
-                "    if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                self.structs[0].string_id
-            );
-            output += &format!("        return alignof({});\n", self.structs[0].ident);
-            for struct_info in &self.structs[1..] {
-                output += &format!(
-                    "    }} else if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                    struct_info.string_id
-                );
-                output += &format!("        return alignof({});\n", struct_info.ident);
-            }
-
-            output += "    } else {\n";
-            output += "        std::abort();\n";
-            output += "    }\n";
-        }
-
-        output += "}\n\n";
-
-        output
+    if let Some(queries) = args.queries {
+        outputs.push((
+            PathBuf::from(queries),
+            ffi_generator.generate(&Backend::Queries {
+                include: include.clone(),
+            })?,
+        ));
     }
 
-    fn gen_component_type(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" ComponentType component_type(const char* string_id) {\n";
-
-        if self.structs.is_empty() {
-            output += "    std::abort();\n";
-        } else {
-            for (i, struct_info) in self.structs.iter().enumerate() {
-                if i == 0 {
-                    output += &format!(
-                        "    if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                        self.structs[0].string_id
-                    );
-                } else {
-                    output += &format!(
-                        "    }} else if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                        struct_info.string_id
-                    );
-                }
-
-                match &struct_info.struct_type {
-                    StructType::Component => {
-                        output += &format!(
-                            "        static_assert(std::is_standard_layout_v<{}>);\n",
-                            struct_info.ident
-                        );
-                        output += &format!(
-                            "        static_assert(std::is_trivially_copyable_v<{}>);\n",
-                            struct_info.ident
-                        );
-                        output += "        return ComponentTypeComponent;\n";
-                    }
-                    StructType::Resource => {
-                        output += "        return ComponentTypeResource;\n";
-                    }
-                }
-            }
-
-            output += "    } else {\n";
-            output += "        std::abort();\n";
-            output += "    }\n";
-        }
-
-        output += "}\n\n";
+    // clap only lets the harness be asked for along with the header it includes
+    if let Some(test_harness) = args.test_harness {
+        let header = header.unwrap();
+        let harness = Backend::TestHarness {
+            include,
+            header: header.file_name().unwrap().to_string_lossy().into_owned(),
+        };
 
-        output
+        outputs.push((
+            PathBuf::from(test_harness),
+            ffi_generator.generate(&harness)?,
+        ));
     }
 
-    fn gen_set_component_ids(&self) -> String {
-        struct ComponentInfo<'a> {
-            ident: &'a str,
-            string_id: String,
-        }
-
-        let mut components: Vec<_> = self
-            .systems
-            .iter()
-            .flat_map(|s| &s.inputs)
-            .filter_map(|i| {
-                if !matches!(i.arg_type, ArgType::Query { .. }) {
-                    let string_id = self
-                        .structs
-                        .iter()
-                        .find(|s| s.ident == i.ident)
-                        .map(|s| s.string_id.clone())
-                        .unwrap_or_else(|| String::from("arete_public::") + &i.ident);
-                    Some(ComponentInfo {
-                        ident: &i.ident,
-                        string_id,
-                    })
-                } else {
-                    None
-                }
-            })
-            .chain(self.structs.iter().map(|s| ComponentInfo {
-                ident: &s.ident,
-                string_id: s.string_id.clone(),
-            }))
-            .chain(ARETE_PUBLIC_COMPONENTS.iter().map(|ident| ComponentInfo {
-                ident,
-                string_id: String::from("arete_public::") + ident,
-            }))
-            .collect();
-
-        components.sort_unstable_by(|a, b| a.ident.cmp(b.ident));
-        components.dedup_by(|a, b| a.ident == b.ident);
+    if let Some(depfile) = args.depfile {
+        let mut dependencies = vec![input];
+        dependencies.extend(args.engine_manifest.map(PathBuf::from));
 
-        let mut output = String::new();
+        let targets: Vec<_> = outputs.iter().map(|(path, _)| path.as_path()).collect();
+        let contents = gen_depfile(&targets, &dependencies);
 
-        output += "extern \"C\" void set_component_id(const char* string_id, ComponentId id) {\n";
-
-        if !components.is_empty() {
-            output += &format!(
-                "    if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                components[0].string_id
-            );
-            output += &format!("        Component<{}>::ID = id;\n", components[0].ident);
-
-            for component in &components[1..] {
-                output += &format!(
-                    "    }} else if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                    component.string_id
-                );
-                output += &format!("        Component<{}>::ID = id;\n", component.ident);
-            }
-
-            output += "    }\n";
-        }
-
-        output += "}\n\n";
-
-        output
+        outputs.push((PathBuf::from(depfile), contents));
     }
 
-    fn gen_resource_init(&self) -> String {
-        let resources: Vec<_> = self
-            .structs
+    if args.check {
+        let stale: Vec<_> = outputs
             .iter()
-            .filter(|s| matches!(s.struct_type, StructType::Resource))
+            .filter(|(path, contents)| fs::read_to_string(path).ok().as_ref() != Some(contents))
             .collect();
 
-        let mut output = String::new();
-
-        output += "extern \"C\" int32_t resource_init(const char* string_id, void* val) {\n";
-
-        if resources.is_empty() {
-            output += "    return 1;\n";
-        } else {
-            for (i, resource) in resources.iter().enumerate() {
-                if i == 0 {
-                    output += &format!(
-                        "    if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                        resource.string_id
-                    );
-                } else {
-                    output += &format!(
-                        "    }} else if (std::strcmp(string_id, \"{}\") == 0) {{\n",
-                        resource.string_id
-                    );
-                }
-                output += &format!(
-                    "        std::construct_at(static_cast<{}*>(val));\n",
-                    resource.ident
-                );
-            }
-
-            output += "    } else {\n";
-            output += "        return 1;\n";
-            output += "    }\n\n";
-            output += "    return 0;\n";
+        for (path, _) in &stale {
+            eprintln!("{} is out of date", path.display());
         }
 
-        output += "}\n\n";
-
-        output
-    }
-
-    fn gen_systems(&self) -> String {
-        let mut output = String::new();
-
-        output += &self.gen_system_fn_ffi();
-        output += &self.gen_systems_len();
-        output += &self.gen_system_is_once();
-        output += &self.gen_system_fn();
-        output += &self.gen_system_args_len();
-        output += &self.gen_system_arg_type();
-        output += &self.gen_system_arg_component();
-
-        output += &self.gen_system_query_args_len();
-        output += &self.gen_system_query_arg_type();
-        output += &self.gen_system_query_arg_component();
-
-        output
-    }
-
-    fn gen_system_fn_ffi(&self) -> String {
-        let mut output = String::new();
-
-        let gen_system_fn = &mut |system: &SystemInfo| {
-            output += "int32_t ";
-            output += &system.ident;
-            output += "_ffi(void** input) {\n";
-            output += "    ";
-            output += &system.ident;
-            output += "(\n";
-
-            for (i, input) in system.inputs.iter().enumerate() {
-                if let ArgType::Query { .. } = &input.arg_type {
-                    output += &format!("        {{ input[{i}] }}")
-                } else {
-                    output += "        *static_cast<";
-                    if !input.mutable {
-                        output += "const ";
-                    }
-                    output += &format!("{}*>(input[{i}])", input.ident);
-                }
-
-                if i + 1 < system.inputs.len() {
-                    output += ",\n";
-                } else {
-                    output += "\n";
-                }
-            }
-
-            output += "    );\n\n";
-            output += "    return 0;\n";
-            output += "}\n\n";
-        };
-
-        for system in &self.systems {
-            gen_system_fn(system);
+        if !stale.is_empty() {
+            process::exit(1);
         }
 
-        output
-    }
-
-    fn gen_systems_len(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" size_t systems_len() {\n";
-        output += &format!("    return {};\n", self.systems.len());
-        output += "}\n\n";
-
-        output
+        return Ok(());
     }
 
-    fn gen_system_is_once(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" bool system_is_once(size_t system_index) {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate() {
-            output += &format!("        case {i}: return {};\n", system.is_once);
-        }
-
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
+    for (path, contents) in outputs {
+        arete_codegen::write_if_changed(&path, &contents)?;
     }
 
-    fn gen_system_fn(&self) -> String {
-        let mut output = String::new();
-
-        output += "typedef int32_t (*system_fn_ptr)(void**);\n\n";
-        output += "extern \"C\" system_fn_ptr system_fn(size_t system_index) {\n";
-        output += "    switch (system_index) {\n";
+    Ok(())
+}
 
-        for (i, system) in self.systems.iter().enumerate() {
-            output += &format!("        case {i}: return {}_ffi;\n", system.ident);
-        }
+fn gen_depfile(targets: &[&Path], dependencies: &[PathBuf]) -> String {
+    // spaces would otherwise separate paths
+    let escape = |path: &Path| path.display().to_string().replace(' ', "\\ ");
 
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
+    let targets: Vec<_> = targets.iter().map(|target| escape(target)).collect();
+    let mut output = format!("{}:", targets.join(" "));
 
-        output
+    for dependency in dependencies {
+        output += " \\\n    ";
+        output += &escape(dependency);
     }
 
-    fn gen_system_args_len(&self) -> String {
-        let mut output = String::new();
+    output += "\n";
 
-        output += "extern \"C\" size_t system_args_len(size_t system_index) {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate() {
-            output += &format!("        case {i}: return {};\n", system.inputs.len());
-        }
-
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
+    output
+}
 
-        output
+// `.json` files are manifests written with `--manifest`, directories are source trees whose headers
+// together make up the module, anything else is parsed as a module header
+fn load_manifest(path: &Path, catalogue: &EngineCatalogue) -> Result<ModuleManifest, Error> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return ModuleManifest::load(path);
     }
 
-    fn gen_system_arg_type(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" ArgType system_arg_type(size_t system_index, size_t arg_index) {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate() {
-            output += &format!("        case {i}: switch (arg_index) {{\n");
-
-            for (i, input) in system.inputs.iter().enumerate() {
-                output += &format!("            case {i}: return ArgType");
-                output += match &input.arg_type {
-                    ArgType::DataAccessDirect if input.mutable => "DataAccessMut;\n",
-                    ArgType::DataAccessDirect => "DataAccessRef;\n",
-                    ArgType::DataAccessCell => "DataAccessRef;\n",
-                    ArgType::Query { .. } => "Query;\n",
-                };
-            }
+    let ffi_generator = if path.is_dir() {
+        let mut headers = Vec::new();
+        find_headers(path, &mut headers)?;
+        headers.sort();
 
-            output += "            default: std::abort();\n";
-            output += "        }\n";
-        }
-
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
-    }
-
-    fn gen_system_arg_component(&self) -> String {
-        let string_id = |ident: &str| {
-            self.structs
-                .iter()
-                .find(|s| s.ident == ident)
-                .map(|s| s.string_id.clone())
-                .unwrap_or_else(|| String::from("arete_public::") + ident)
+        let mut ffi_generator = FfiGenerator {
+            catalogue: catalogue.clone(),
+            ..Default::default()
         };
 
-        let mut output = String::new();
-
-        output += "extern \"C\" const char* system_arg_component(size_t system_index, size_t arg_index) {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate() {
-            output += &format!("        case {i}: switch (arg_index) {{\n");
-
-            for (i, input) in system.inputs.iter().enumerate().filter(|(_, input)| {
-                matches!(
-                    input.arg_type,
-                    ArgType::DataAccessDirect | ArgType::DataAccessCell
-                )
-            }) {
-                output += &format!(
-                    "            case {i}: return \"{}\";\n",
-                    string_id(&input.ident)
-                );
-            }
-
-            output += "            default: std::abort();\n";
-            output += "        }\n";
-        }
-
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
-    }
-
-    fn gen_system_query_args_len(&self) -> String {
-        let mut output = String::new();
-
-        output +=
-            "extern \"C\" size_t system_query_args_len(size_t system_index, size_t arg_index) {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate().filter(|(_, system)| {
-            system
-                .inputs
-                .iter()
-                .any(|input| matches!(input.arg_type, ArgType::Query { .. }))
-        }) {
-            output += &format!("        case {i}: switch (arg_index) {{\n");
-
-            for (i, input) in system.inputs.iter().enumerate() {
-                if let ArgType::Query { inputs } = &input.arg_type {
-                    output += &format!("            case {i}: return {};\n", inputs.len());
-                }
-            }
-
-            output += "            default: std::abort();\n";
-            output += "        }\n";
+        for header in headers {
+            let parsed = arete_codegen::parse_file(&header, catalogue.clone())?;
+            ffi_generator.structs.extend(parsed.structs);
+            ffi_generator.systems.extend(parsed.systems);
         }
 
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
-    }
-
-    fn gen_system_query_arg_type(&self) -> String {
-        let mut output = String::new();
-
-        output += "extern \"C\" ArgType system_query_arg_type(\n";
-        output += "    size_t system_index,\n";
-        output += "    size_t arg_index,\n";
-        output += "    size_t query_index\n";
-        output += ") {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate().filter(|(_, system)| {
-            system
-                .inputs
-                .iter()
-                .any(|input| matches!(input.arg_type, ArgType::Query { .. }))
-        }) {
-            output += &format!("        case {i}: switch (arg_index) {{\n");
-
-            for (i, input) in system.inputs.iter().enumerate() {
-                if let ArgType::Query { inputs } = &input.arg_type {
-                    output += &format!("            case {i}: switch (query_index) {{\n");
-
-                    for (i, input) in inputs.iter().enumerate() {
-                        output += &format!("                case {i}: return ArgTypeDataAccess");
-                        output += match input.mutable {
-                            true => "Mut;\n",
-                            false => "Ref;\n",
-                        };
-                    }
-
-                    output += "                default: std::abort();\n";
-                    output += "            }\n";
-                }
-            }
-
-            output += "            default: std::abort();\n";
-            output += "        }\n";
-        }
+        ffi_generator
+    } else {
+        arete_codegen::parse_file(path, catalogue.clone())?
+    };
 
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
+    ffi_generator.validate()?;
+    Ok(ffi_generator.manifest())
+}
 
-        output
-    }
+fn find_headers(dir: &Path, headers: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let io_error = |source| Error::Io {
+        path: dir.to_owned(),
+        source,
+    };
 
-    fn gen_system_query_arg_component(&self) -> String {
-        let string_id = |ident: &str| {
-            self.structs
-                .iter()
-                .find(|s| s.ident == ident)
-                .map(|s| s.string_id.clone())
-                .unwrap_or_else(|| String::from("arete_public::") + ident)
-        };
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
 
-        let mut output = String::new();
-
-        output += "extern \"C\" const char* system_query_arg_component(\n";
-        output += "    size_t system_index,\n";
-        output += "    size_t arg_index,\n";
-        output += "    size_t query_index\n";
-        output += ") {\n";
-        output += "    switch (system_index) {\n";
-
-        for (i, system) in self.systems.iter().enumerate().filter(|(_, system)| {
-            system
-                .inputs
-                .iter()
-                .any(|input| matches!(input.arg_type, ArgType::Query { .. }))
-        }) {
-            output += &format!("        case {i}: switch (arg_index) {{\n");
-
-            for (i, input) in system.inputs.iter().enumerate() {
-                if let ArgType::Query { inputs } = &input.arg_type {
-                    output += &format!("            case {i}: switch (query_index) {{\n");
-
-                    for (i, input) in inputs.iter().enumerate() {
-                        output += &format!(
-                            "                case {i}: return \"{}\";\n",
-                            string_id(&input.ident)
-                        );
-                    }
-
-                    output += "                default: std::abort();\n";
-                    output += "            }\n";
-                }
-            }
-
-            output += "            default: std::abort();\n";
-            output += "        }\n";
+        if path.is_dir() {
+            find_headers(&path, headers)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "h" || ext == "hpp")
+        {
+            headers.push(path);
         }
-
-        output += "        default: std::abort();\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
-    }
-
-    fn gen_callbacks(&self) -> String {
-        let mut output = String::new();
-
-        output += "enum CallbackType {\n";
-        output += "    CallbackTypeQueryGetFn,\n";
-        output += "    CallbackTypeQueryGetMutFn,\n";
-        output += "    CallbackTypeQueryGetFirstFn,\n";
-        output += "    CallbackTypeQueryGetFirstMutFn,\n";
-        output += "    CallbackTypeQueryForEachFn,\n";
-        output += "    CallbackTypeQueryParForEachFn,\n";
-        output += "};\n\n";
-
-        output += "const void* (*QueryGetFn)(const void*, EntityId, ComponentId);\n";
-        output += "void* (*QueryGetMutFn)(void*, EntityId, ComponentId);\n";
-        output += "const void* (*QueryGetFirstFn)(const void*, ComponentId);\n";
-        output += "void* (*QueryGetFirstMutFn)(void*, ComponentId);\n";
-        output += "void (*QueryForEachFn)(void*, QueryForEachCallback, void*);\n";
-        output += "void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);\n\n";
-
-        output += "extern \"C\" void set_callback_fn(\n";
-        output += "    CallbackType callback_type,\n";
-        output += "    const void* callback\n";
-        output += ") {\n";
-        output += "    switch (callback_type) {\n";
-        output += "    case CallbackTypeQueryGetFn:\n";
-        output += "        QueryGetFn = (const void*(*)(const void*, EntityId, ComponentId))(callback);\n";
-        output += "        break;\n";
-        output += "    case CallbackTypeQueryGetMutFn:\n";
-        output += "        QueryGetMutFn = (void*(*)(void*, EntityId, ComponentId))(callback);\n";
-        output += "        break;\n";
-        output += "    case CallbackTypeQueryGetFirstFn:\n";
-        output +=
-            "        QueryGetFirstFn = (const void*(*)(const void*, ComponentId))(callback);\n";
-        output += "        break;\n";
-        output += "    case CallbackTypeQueryGetFirstMutFn:\n";
-        output += "        QueryGetFirstMutFn = (void*(*)(void*, ComponentId))(callback);\n";
-        output += "        break;\n";
-        output += "    case CallbackTypeQueryForEachFn:\n";
-        output +=
-            "        QueryForEachFn = (void(*)(void*, QueryForEachCallback, void*))(callback);\n";
-        output += "        break;\n";
-        output += "    case CallbackTypeQueryParForEachFn:\n";
-        output += "        QueryParForEachFn = (void(*)(void*, QueryParForEachCallback, const void*))(callback);\n";
-        output += "        break;\n";
-        output += "    }\n";
-        output += "}\n\n";
-
-        output
     }
-}
-
-fn gen_version() -> String {
-    let mut output = String::new();
 
-    output += "extern \"C\" uint32_t arete_target_version() {\n";
-    output += "    return ENGINE_VERSION;\n";
-    output += "}\n\n";
-
-    output
+    Ok(())
 }
@@ -0,0 +1,303 @@
+use std::path::Path;
+use std::{fmt, fs, ptr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ArgType, Error, FfiGenerator, SourceLocation, StructType, SystemInputInfo};
+
+// machine-readable description of the module, written with `--manifest`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModuleManifest {
+    pub components: Vec<ManifestStruct>,
+    pub systems: Vec<ManifestSystem>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestStruct {
+    pub ident: String,
+    pub string_id: String,
+    pub kind: StructType,
+    pub source: SourceLocation,
+    pub fields: Vec<ManifestField>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestField {
+    pub ident: String,
+    pub ty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_len: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestSystem {
+    pub ident: String,
+    pub schedule: Schedule,
+    pub source: SourceLocation,
+    pub params: Vec<ManifestParam>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Schedule {
+    Once,
+    Frame,
+}
+
+// `ident` and `string_id` are absent for queries, whose components are listed in `terms`
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestParam {
+    pub access: Access,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ident: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub string_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub terms: Vec<ManifestTerm>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestTerm {
+    pub access: Access,
+    pub ident: String,
+    pub string_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Mut,
+    Ref,
+    Query,
+}
+
+// a difference between two versions of a module, as reported by the `diff` subcommand
+#[derive(Debug)]
+pub struct Change {
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl ModuleManifest {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let manifest = fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+        serde_json::from_str(&manifest).map_err(|err| Error::json(path, err))
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let compatibility = if self.breaking {
+            "breaking"
+        } else {
+            "compatible"
+        };
+
+        write!(f, "{compatibility}: {}", self.description)
+    }
+}
+
+// Saved worlds refer to components by string id and store their fields as laid out in memory, and
+// the engine addresses systems by index, so anything that disturbs either breaks compatibility.
+pub fn diff_manifests(old: &ModuleManifest, new: &ModuleManifest) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let mut change = |breaking, description| {
+        changes.push(Change {
+            breaking,
+            description,
+        })
+    };
+
+    // Saves find components by string id, so that's what pairs them up. A component whose string
+    // id changed is recognized by its ident, or failing that by an identical layout.
+    let mut pairs = Vec::new();
+    let mut unpaired: Vec<_> = old.components.iter().collect();
+    let mut added: Vec<_> = new.components.iter().collect();
+
+    let same_string_id = |a: &ManifestStruct, b: &ManifestStruct| a.string_id == b.string_id;
+    let same_ident = |a: &ManifestStruct, b: &ManifestStruct| a.ident == b.ident;
+    let same_layout = |a: &ManifestStruct, b: &ManifestStruct| {
+        a.kind == b.kind && !a.fields.is_empty() && a.fields == b.fields
+    };
+
+    for matches in [same_string_id, same_ident, same_layout] {
+        unpaired.retain(|old_struct| {
+            let Some(i) = added
+                .iter()
+                .position(|new_struct| matches(old_struct, new_struct))
+            else {
+                return true;
+            };
+
+            pairs.push((*old_struct, added.remove(i)));
+            false
+        });
+    }
+
+    for old_struct in &old.components {
+        let ident = &old_struct.ident;
+        let kind = struct_type_name(old_struct.kind);
+
+        let Some(&(_, new_struct)) = pairs
+            .iter()
+            .find(|(paired, _)| ptr::eq(*paired, old_struct))
+        else {
+            change(true, format!("{kind} {ident} was removed"));
+            continue;
+        };
+
+        if new_struct.kind != old_struct.kind {
+            change(
+                true,
+                format!(
+                    "{ident} changed from a {kind} to a {}",
+                    struct_type_name(new_struct.kind)
+                ),
+            );
+        }
+
+        if new_struct.string_id != old_struct.string_id {
+            change(
+                true,
+                format!(
+                    "{kind} {ident} changed string id from {} to {}",
+                    old_struct.string_id, new_struct.string_id
+                ),
+            );
+        }
+
+        if new_struct.fields != old_struct.fields {
+            change(true, format!("{kind} {ident} changed its fields"));
+        }
+    }
+
+    for new_struct in added {
+        change(
+            false,
+            format!(
+                "{} {} was added",
+                struct_type_name(new_struct.kind),
+                new_struct.ident
+            ),
+        );
+    }
+
+    for (old_index, old_system) in old.systems.iter().enumerate() {
+        let ident = &old_system.ident;
+
+        let Some(new_index) = new.systems.iter().position(|s| s.ident == *ident) else {
+            change(true, format!("system {ident} was removed"));
+            continue;
+        };
+
+        let new_system = &new.systems[new_index];
+
+        if new_index != old_index {
+            change(
+                true,
+                format!("system {ident} moved from index {old_index} to {new_index}"),
+            );
+        }
+
+        if new_system.schedule != old_system.schedule {
+            change(true, format!("system {ident} changed its schedule"));
+        }
+
+        if new_system.params != old_system.params {
+            change(true, format!("system {ident} changed its parameters"));
+        }
+    }
+
+    for new_system in &new.systems {
+        if !old.systems.iter().any(|s| s.ident == new_system.ident) {
+            change(false, format!("system {} was added", new_system.ident));
+        }
+    }
+
+    changes
+}
+
+fn struct_type_name(struct_type: StructType) -> &'static str {
+    match struct_type {
+        StructType::Component => "component",
+        StructType::Resource => "resource",
+    }
+}
+
+fn access(input: &SystemInputInfo) -> Access {
+    match &input.arg_type {
+        ArgType::DataAccessDirect if input.mutable => Access::Mut,
+        ArgType::DataAccessDirect => Access::Ref,
+        ArgType::DataAccessCell => Access::Ref,
+        ArgType::Query { .. } => Access::Query,
+    }
+}
+
+impl FfiGenerator {
+    pub fn manifest(&self) -> ModuleManifest {
+        let components = self
+            .structs
+            .iter()
+            .map(|s| ManifestStruct {
+                ident: s.ident.clone(),
+                string_id: s.string_id.clone(),
+                kind: s.struct_type,
+                source: s.source.clone(),
+                fields: s
+                    .fields
+                    .iter()
+                    .map(|field| ManifestField {
+                        ident: field.ident.clone(),
+                        ty: field.ty.clone(),
+                        array_len: field.array_len.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let systems = self
+            .systems
+            .iter()
+            .map(|system| ManifestSystem {
+                ident: system.ident.clone(),
+                schedule: if system.is_once {
+                    Schedule::Once
+                } else {
+                    Schedule::Frame
+                },
+                source: system.source.clone(),
+                params: system
+                    .inputs
+                    .iter()
+                    .map(|input| match &input.arg_type {
+                        ArgType::Query { inputs } => ManifestParam {
+                            access: Access::Query,
+                            ident: None,
+                            string_id: None,
+                            terms: inputs
+                                .iter()
+                                .map(|input| ManifestTerm {
+                                    access: access(input),
+                                    ident: input.ident.clone(),
+                                    string_id: self.string_id(&input.ident),
+                                })
+                                .collect(),
+                        },
+                        _ => ManifestParam {
+                            access: access(input),
+                            ident: Some(input.ident.clone()),
+                            string_id: Some(self.string_id(&input.ident)),
+                            terms: Vec::new(),
+                        },
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        ModuleManifest {
+            components,
+            systems,
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+pub(crate) const ARETE_PUBLIC_COMPONENTS: &[&str] = &[
+    "Camera",
+    "Color",
+    "DirectionalLight",
+    "DynamicStaticMesh",
+    "PointLight",
+    "Transform",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StructType {
+    Component,
+    Resource,
+}
+
+#[derive(Debug)]
+pub enum ArgType {
+    DataAccessDirect,
+    DataAccessCell,
+    Query { inputs: Vec<SystemInputInfo> },
+}
+
+// What a system's return value means to the engine. Zero is success, anything else is an error
+// code the engine can look up with `system_error_name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReturnType {
+    Void,
+    // `false` is error code 1
+    Bool,
+    AreteResult,
+    // the enumerators are only known when the enum is declared in the module header
+    Enum {
+        ident: String,
+        variants: Vec<String>,
+    },
+}
+
+#[derive(Debug)]
+pub struct SystemInfo {
+    pub ident: String,
+    pub is_once: bool,
+    pub return_type: ReturnType,
+    pub inputs: Vec<SystemInputInfo>,
+    pub source: SourceLocation,
+}
+
+#[derive(Debug)]
+pub struct SystemInputInfo {
+    pub ident: String,
+    pub arg_type: ArgType,
+    pub mutable: bool,
+}
+
+#[derive(Debug)]
+pub struct StructInfo {
+    pub ident: String,
+    pub string_id: String,
+    pub struct_type: StructType,
+    pub fields: Vec<FieldInfo>,
+    // a member that may take up space couldn't be parsed, so `fields` doesn't describe the layout
+    pub has_skipped_members: bool,
+    pub source: SourceLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EngineCatalogue {
+    pub engine_version: Option<u32>,
+    pub public: Vec<PublicStructInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublicStructInfo {
+    #[serde(rename = "name")]
+    pub ident: String,
+    pub string_id: String,
+    #[serde(rename = "kind")]
+    pub struct_type: StructType,
+    pub size: Option<usize>,
+    pub align: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct FieldInfo {
+    pub ident: String,
+    pub ty: String,
+    pub array_len: Option<String>,
+}
+
+impl StructInfo {
+    pub fn has_entity_fields(&self) -> bool {
+        self.fields.iter().any(FieldInfo::is_entity)
+    }
+}
+
+impl SystemInfo {
+    pub fn has_error_names(&self) -> bool {
+        match &self.return_type {
+            ReturnType::Bool => true,
+            ReturnType::Enum { variants, .. } => !variants.is_empty(),
+            ReturnType::Void | ReturnType::AreteResult => false,
+        }
+    }
+}
+
+impl EngineCatalogue {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let manifest = fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+        serde_json::from_str(&manifest).map_err(|err| Error::json(path, err))
+    }
+
+    pub fn find(&self, ident: &str) -> Option<&PublicStructInfo> {
+        self.public.iter().find(|s| s.ident == ident)
+    }
+}
+
+// Only used when no engine manifest is given. Engine resources differ between engines, so they
+// only come from the manifest.
+impl Default for EngineCatalogue {
+    fn default() -> Self {
+        Self {
+            engine_version: None,
+            public: ARETE_PUBLIC_COMPONENTS
+                .iter()
+                .map(|ident| PublicStructInfo {
+                    ident: ident.to_string(),
+                    string_id: String::from("arete_public::") + ident,
+                    struct_type: StructType::Component,
+                    size: None,
+                    align: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FieldInfo {
+    pub fn is_entity(&self) -> bool {
+        self.ty == "EntityId"
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FfiGenerator {
+    pub systems: Vec<SystemInfo>,
+    pub structs: Vec<StructInfo>,
+    pub catalogue: EngineCatalogue,
+}
+
+impl FfiGenerator {
+    // components are accessed through queries, resources are passed to systems directly
+    pub fn validate(&self) -> Result<(), Error> {
+        // the string id lookup can't tell duplicates apart
+        for (i, struct_info) in self.structs.iter().enumerate() {
+            if self.structs[..i]
+                .iter()
+                .any(|s| s.string_id == struct_info.string_id)
+            {
+                return Err(Error::Invalid {
+                    source: struct_info.source.clone(),
+                    message: format!("{} is declared more than once", struct_info.string_id),
+                });
+            }
+        }
+
+        for system in &self.systems {
+            for input in &system.inputs {
+                let terms = match &input.arg_type {
+                    ArgType::Query { inputs } => inputs.as_slice(),
+                    _ => std::slice::from_ref(input),
+                };
+
+                // otherwise its string id would have to be guessed
+                if let Some(term) = terms.iter().find(|t| self.struct_type(&t.ident).is_none()) {
+                    return Err(Error::Invalid {
+                        source: system.source.clone(),
+                        message: format!(
+                            "system {} takes {}, which is neither declared by the module nor in the engine catalogue",
+                            system.ident, term.ident
+                        ),
+                    });
+                }
+
+                if let ArgType::Query { inputs } = &input.arg_type {
+                    for input in inputs {
+                        if self.struct_type(&input.ident) == Some(StructType::Resource) {
+                            return Err(Error::Invalid {
+                                source: system.source.clone(),
+                                message: format!(
+                                    "system {} queries {}, which is a resource",
+                                    system.ident, input.ident
+                                ),
+                            });
+                        }
+                    }
+                } else if self.struct_type(&input.ident) == Some(StructType::Component) {
+                    return Err(Error::Invalid {
+                        source: system.source.clone(),
+                        message: format!(
+                            "system {} takes {} directly, which is a component and must be queried",
+                            system.ident, input.ident
+                        ),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn struct_type(&self, ident: &str) -> Option<StructType> {
+        self.structs
+            .iter()
+            .find(|s| s.ident == ident)
+            .map(|s| s.struct_type)
+            .or_else(|| self.catalogue.find(ident).map(|s| s.struct_type))
+    }
+
+    // The engine types the module's systems take, in string id order. Generated code only refers to
+    // these, so the engine's headers don't need to declare anything else in the catalogue.
+    pub(crate) fn public_inputs(&self) -> Vec<&PublicStructInfo> {
+        let mut public: Vec<_> = self
+            .systems
+            .iter()
+            .flat_map(|system| &system.inputs)
+            .flat_map(|input| match &input.arg_type {
+                ArgType::Query { inputs } => inputs.as_slice(),
+                _ => std::slice::from_ref(input),
+            })
+            .filter(|input| !self.structs.iter().any(|s| s.ident == input.ident))
+            .filter_map(|input| self.catalogue.find(&input.ident))
+            .collect();
+
+        public.sort_unstable_by(|a, b| a.string_id.cmp(&b.string_id));
+        public.dedup_by(|a, b| a.string_id == b.string_id);
+
+        public
+    }
+
+    pub(crate) fn string_id(&self, ident: &str) -> String {
+        self.structs
+            .iter()
+            .find(|s| s.ident == ident)
+            .map(|s| s.string_id.clone())
+            .or_else(|| self.catalogue.find(ident).map(|s| s.string_id.clone()))
+            .unwrap_or_else(|| panic!("{ident} isn't declared, the module must be validated first"))
+    }
+}
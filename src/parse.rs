@@ -0,0 +1,484 @@
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::{
+    ArgType, EngineCatalogue, Error, FfiGenerator, FieldInfo, ReturnType, SourceLocation,
+    StructInfo, StructType, SystemInfo, SystemInputInfo,
+};
+
+// Only the file name is kept as the source name, so generated files don't depend on where the
+// module was checked out.
+pub fn parse_file(path: &Path, catalogue: EngineCatalogue) -> Result<FfiGenerator, Error> {
+    let source = fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+    let source_name = path.file_name().unwrap_or(path.as_os_str());
+
+    parse_source(&source, &source_name.to_string_lossy(), catalogue)
+}
+
+// `source_name` is only used to report where things were declared, so unsaved editor buffers can
+// be parsed too
+pub fn parse_source(
+    source: &str,
+    source_name: &str,
+    catalogue: EngineCatalogue,
+) -> Result<FfiGenerator, Error> {
+    let mut info = FfiGenerator {
+        catalogue,
+        ..Default::default()
+    };
+
+    let file = strip_comments(source);
+
+    let source = |offset| source_location(source_name, &file, offset);
+
+    let re = Regex::new("COMPONENT\\(\\s*(\\S+)\\s*\\)").unwrap();
+    for component in re.captures_iter(&file) {
+        let ident = component.get(1).unwrap().as_str();
+        let (offset, fields, has_skipped_members) = parse_struct_definition(&file, ident);
+
+        parse_struct(
+            &mut info,
+            ident,
+            false,
+            fields,
+            has_skipped_members,
+            source(offset.unwrap_or(component.get(0).unwrap().start())),
+        );
+    }
+
+    let re = Regex::new("RESOURCE\\(\\s*(\\S+)\\s*\\)").unwrap();
+    for resource in re.captures_iter(&file) {
+        let ident = resource.get(1).unwrap().as_str();
+        let (offset, fields, has_skipped_members) = parse_struct_definition(&file, ident);
+
+        parse_struct(
+            &mut info,
+            ident,
+            true,
+            fields,
+            has_skipped_members,
+            source(offset.unwrap_or(resource.get(0).unwrap().start())),
+        );
+    }
+
+    let re = Regex::new("SYSTEM_ONCE\\(([\\s\\S]*?)(\\S+)\\s*,([\\s\\S]+?)\\)").unwrap();
+    for system in re.captures_iter(&file) {
+        parse_system(
+            &mut info,
+            system.get(2).unwrap().as_str(),
+            parse_return_type(&file, system.get(1).unwrap().as_str()),
+            system.get(3).unwrap().as_str(),
+            true,
+            source(system.get(0).unwrap().start()),
+        )?;
+    }
+
+    let re = Regex::new("SYSTEM\\(([\\s\\S]*?)(\\S+)\\s*,([\\s\\S]+?)\\)").unwrap();
+    for system in re.captures_iter(&file) {
+        parse_system(
+            &mut info,
+            system.get(2).unwrap().as_str(),
+            parse_return_type(&file, system.get(1).unwrap().as_str()),
+            system.get(3).unwrap().as_str(),
+            false,
+            source(system.get(0).unwrap().start()),
+        )?;
+    }
+
+    Ok(info)
+}
+
+// comments are stripped, but line breaks are kept so offsets still map to source lines
+fn strip_comments(source: &str) -> String {
+    let mut file = String::new();
+
+    for line in source.lines() {
+        file += line.find("//").map(|i| &line[..i]).unwrap_or(line);
+        file += "\n";
+    }
+
+    while let Some(i_start) = file.find("/*") {
+        let Some(i_end) = file[i_start..].find("*/") else {
+            // ??
+            break;
+        };
+
+        let range = i_start..=i_start + i_end + 1;
+        let line_breaks = "\n".repeat(file[range.clone()].matches('\n').count());
+        file.replace_range(range, &line_breaks);
+    }
+
+    file
+}
+
+fn parse_system(
+    info: &mut FfiGenerator,
+    ident: &str,
+    return_type: ReturnType,
+    body: &str,
+    is_once: bool,
+    source: SourceLocation,
+) -> Result<(), Error> {
+    let mut inputs = Vec::new();
+
+    for param in split_top_level(body.trim(), ',') {
+        let (mutable, param) = strip_const(param.trim());
+
+        if let Some(query) = param.strip_prefix("Query") {
+            let terms = query
+                .trim_start()
+                .strip_prefix('<')
+                .and_then(|query| query.rfind('>').map(|end| &query[..end]))
+                .ok_or_else(|| parse_error(&source, format!("malformed query `{param}`")))?;
+
+            let mut query_inputs = Vec::new();
+
+            for term in split_top_level(terms, ',') {
+                let (mutable, term) = strip_const(term.trim());
+                let ident = reference_ident(term).ok_or_else(|| {
+                    parse_error(
+                        &source,
+                        format!("query term `{term}` must be taken as a reference"),
+                    )
+                })?;
+
+                query_inputs.push(SystemInputInfo {
+                    ident,
+                    arg_type: ArgType::DataAccessDirect,
+                    mutable,
+                });
+            }
+
+            inputs.push(SystemInputInfo {
+                ident: "query".to_owned() + &inputs.len().to_string(),
+                arg_type: ArgType::Query {
+                    inputs: query_inputs,
+                },
+                mutable,
+            });
+        } else {
+            let ident = reference_ident(param).ok_or_else(|| {
+                parse_error(
+                    &source,
+                    format!("parameter `{param}` of {ident} must be taken as a reference"),
+                )
+            })?;
+
+            inputs.push(SystemInputInfo {
+                ident,
+                arg_type: ArgType::DataAccessDirect,
+                mutable,
+            });
+        }
+    }
+
+    info.systems.push(SystemInfo {
+        ident: ident.to_owned(),
+        is_once,
+        return_type,
+        inputs,
+        source,
+    });
+
+    Ok(())
+}
+
+fn strip_const(param: &str) -> (bool, &str) {
+    match param.strip_prefix("const") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => (false, rest.trim_start()),
+        _ => (true, param),
+    }
+}
+
+// `Velocity& velocity` -> `Velocity`
+fn reference_ident(param: &str) -> Option<String> {
+    let (ident, _) = param.split_once('&')?;
+    let ident = ident.trim_end();
+
+    (!ident.is_empty()).then(|| ident.to_owned())
+}
+
+fn parse_error(source: &SourceLocation, message: String) -> Error {
+    Error::Parse {
+        source: source.clone(),
+        message,
+    }
+}
+
+// anything other than `void`, `bool` or `AreteResult` is taken to be an error enum, which the
+// generated code checks
+fn parse_return_type(file: &str, ty: &str) -> ReturnType {
+    match ty.split_whitespace().collect::<Vec<_>>().join(" ").as_str() {
+        "void" => ReturnType::Void,
+        "bool" => ReturnType::Bool,
+        "AreteResult" => ReturnType::AreteResult,
+        ident => ReturnType::Enum {
+            ident: ident.to_owned(),
+            variants: parse_enum_variants(file, ident),
+        },
+    }
+}
+
+// Like structs, the enum may be defined in another header, in which case no variants are known.
+fn parse_enum_variants(file: &str, ident: &str) -> Vec<String> {
+    // only the last path segment is declared in the enum itself
+    let name = ident.rsplit("::").next().unwrap();
+
+    let re = Regex::new(&format!(
+        "\\benum\\s+(?:(?:class|struct)\\s+)?{}\\s*(?::[^{{;]*)?\\{{([^}}]*)\\}}",
+        regex::escape(name)
+    ))
+    .unwrap();
+
+    let Some(definition) = re.captures(file) else {
+        return Vec::new();
+    };
+
+    split_top_level(&definition[1], ',')
+        .into_iter()
+        .map(|variant| strip_initializer(variant).to_owned())
+        .filter(|variant| !variant.is_empty())
+        .collect()
+}
+
+fn parse_struct(
+    info: &mut FfiGenerator,
+    ident: &str,
+    is_resource: bool,
+    fields: Vec<FieldInfo>,
+    has_skipped_members: bool,
+    source: SourceLocation,
+) {
+    let struct_type = if is_resource {
+        StructType::Resource
+    } else {
+        StructType::Component
+    };
+
+    info.structs.push(StructInfo {
+        ident: ident.to_string(),
+        string_id: String::from("game_module::") + ident,
+        struct_type,
+        fields,
+        has_skipped_members,
+        source,
+    });
+}
+
+// The struct may be defined in another header, in which case no fields are known. Otherwise the
+// offset of the definition is returned alongside its fields, and whether any member that may take
+// up space couldn't be parsed.
+fn parse_struct_definition(file: &str, ident: &str) -> (Option<usize>, Vec<FieldInfo>, bool) {
+    let re = Regex::new(&format!(
+        "\\b(?:struct|class)\\s+{}\\s*(?:final\\s*)?(?::[^{{;]*)?\\{{",
+        regex::escape(ident)
+    ))
+    .unwrap();
+
+    let Some(definition) = re.find(file) else {
+        return (None, Vec::new(), false);
+    };
+
+    let body_start = definition.end();
+
+    let mut depth = 1;
+    for (i, c) in file[body_start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let body = &file[body_start..body_start + i];
+                    let (fields, has_skipped_members) = parse_struct_fields(body);
+                    return (Some(definition.start()), fields, has_skipped_members);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (Some(definition.start()), Vec::new(), true)
+}
+
+fn source_location(file_name: &str, file: &str, offset: usize) -> SourceLocation {
+    SourceLocation {
+        file: file_name.to_owned(),
+        line: file[..offset].matches('\n').count() + 1,
+    }
+}
+
+fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut quote = None;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if quote == Some(c) => quote = None,
+            _ if quote.is_some() => {}
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&s[start..]);
+    parts
+}
+
+// Only data members are returned, anything which doesn't contribute to the layout is skipped. Data
+// members that can't be parsed, like anonymous unions, function pointers or `alignas` members, are
+// skipped too, but reported so the fields aren't taken for the whole layout.
+fn parse_struct_fields(body: &str) -> (Vec<FieldInfo>, bool) {
+    let mut statements = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut quote = None;
+
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if quote == Some(c) => quote = None,
+            _ if quote.is_some() => {}
+            '"' | '\'' => quote = Some(c),
+            '{' | '(' => depth += 1,
+            ')' => depth -= 1,
+            '}' => {
+                depth -= 1;
+                // member function definitions end with a closing brace rather than a semicolon
+                if depth == 0 && body[start..i].contains('(') {
+                    statements.push(&body[start..=i]);
+                    start = i + 1;
+                }
+            }
+            ';' if depth == 0 => {
+                statements.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let mut fields = Vec::new();
+    let mut has_skipped_members = false;
+
+    for mut statement in statements {
+        statement = statement.trim();
+
+        for access in ["public:", "protected:", "private:"] {
+            if let Some(rest) = statement.strip_prefix(access) {
+                statement = rest.trim_start();
+            }
+        }
+
+        let first_word = statement.split_whitespace().next().unwrap_or("");
+        if matches!(
+            first_word,
+            "" | "static" | "constexpr" | "using" | "typedef" | "friend" | "template"
+        ) {
+            continue;
+        }
+
+        if matches!(first_word, "struct" | "class" | "enum" | "union") {
+            has_skipped_members |= !is_nested_type(statement);
+            continue;
+        }
+
+        if statement.contains("alignas") || statement.contains("[[") {
+            has_skipped_members = true;
+            continue;
+        }
+
+        let mut declarators = split_top_level(statement, ',').into_iter();
+
+        let first = strip_initializer(declarators.next().unwrap());
+        if let Some((_, after)) = first.split_once('(') {
+            // `void (*callback)(int)` is a member, `float length() const` isn't
+            has_skipped_members |= after.trim_start().starts_with(['*', '&']);
+            continue;
+        }
+
+        let (base_ty, declarator) = split_declaration(first);
+        if base_ty.is_empty() {
+            continue;
+        }
+
+        fields.push(parse_declarator(&base_ty, declarator));
+
+        for declarator in declarators {
+            fields.push(parse_declarator(&base_ty, strip_initializer(declarator)));
+        }
+    }
+
+    (fields, has_skipped_members)
+}
+
+// A named nested type, or a forward declaration of one, only declares a type. Anonymous ones and
+// those declaring a member along with the type are data.
+fn is_nested_type(statement: &str) -> bool {
+    let Some(body_start) = statement.find('{') else {
+        return true;
+    };
+
+    let head: Vec<_> = statement[..body_start]
+        .split(':')
+        .next()
+        .unwrap()
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "struct" | "class" | "enum" | "union" | "final"))
+        .collect();
+
+    !head.is_empty() && statement.ends_with('}')
+}
+
+fn strip_initializer(declarator: &str) -> &str {
+    let end = declarator.find(['=', '{']).unwrap_or(declarator.len());
+    declarator[..end].trim()
+}
+
+// splits `const Foo* bar[4]` into the base type `const Foo` and the declarator `* bar[4]`
+fn split_declaration(declaration: &str) -> (String, &str) {
+    let name_end = declaration.find('[').unwrap_or(declaration.len());
+    let name_start = declaration[..name_end]
+        .trim_end()
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    let base_ty = declaration[..name_start].trim_end_matches(['*', '&', ' ']);
+
+    (
+        base_ty.split_whitespace().collect::<Vec<_>>().join(" "),
+        &declaration[base_ty.len()..],
+    )
+}
+
+fn parse_declarator(base_ty: &str, declarator: &str) -> FieldInfo {
+    let (declarator, array_len) = match declarator.split_once('[') {
+        Some((declarator, len)) => (
+            declarator,
+            Some(len.trim_end().trim_end_matches(']').trim().to_owned()),
+        ),
+        None => (declarator, None),
+    };
+
+    let declarator = declarator.trim();
+    let ident = declarator.trim_start_matches(['*', '&', ' ']);
+    let indirection: String = declarator[..declarator.len() - ident.len()]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    FieldInfo {
+        ident: ident.to_owned(),
+        ty: base_ty.to_owned() + &indirection,
+        array_len,
+    }
+}
@@ -0,0 +1,216 @@
+use crate::abi::CALLBACKS;
+use crate::{ArgType, FfiGenerator, SystemInputInfo};
+
+impl FfiGenerator {
+    // A `TypedQuery` for every signature the module's systems take, wrapping the engine's `Query`
+    // argument so systems iterate and fetch typed components instead of going through the raw
+    // callbacks. It's a template of its own rather than specializations of `Query`, so a source
+    // that doesn't include it gets an incomplete type instead of silently using the engine's.
+    pub(crate) fn gen_queries(&self, include: &str) -> String {
+        let mut output = String::new();
+
+        output += "#pragma once\n\n";
+
+        output += &format!("#include {include:?}\n");
+        output += "#include <cstddef>\n";
+        output += "#include <cstdint>\n";
+        output += "#include <cstdio>\n";
+        output += "#include <optional>\n";
+        output += "#include <tuple>\n\n";
+
+        output += "// defined in ffi.cpp, and set by the engine through module_init\n";
+        for callback in CALLBACKS {
+            output += &format!(
+                "extern {} (*{})({});\n",
+                callback.ret, callback.ident, callback.args
+            );
+        }
+        output += "\n";
+
+        output += "template <typename... Ts>\n";
+        output += "struct TypedQuery;\n\n";
+
+        output +=
+            "// `TypedQuery movers(query);` picks the specialization for the system's argument\n";
+        output += "template <typename... Ts>\n";
+        output += "TypedQuery(Query<Ts...>) -> TypedQuery<Ts...>;\n\n";
+
+        for terms in self.query_signatures() {
+            output += &gen_query(terms);
+        }
+
+        output
+    }
+
+    // in order of first use, each signature once
+    fn query_signatures(&self) -> Vec<&[SystemInputInfo]> {
+        let mut signatures: Vec<&[SystemInputInfo]> = Vec::new();
+
+        for input in self.systems.iter().flat_map(|system| &system.inputs) {
+            let ArgType::Query { inputs } = &input.arg_type else {
+                continue;
+            };
+
+            let is_seen = signatures
+                .iter()
+                .any(|seen| query_args(seen) == query_args(inputs));
+
+            if !is_seen {
+                signatures.push(inputs);
+            }
+        }
+
+        signatures
+    }
+}
+
+fn term_type(term: &SystemInputInfo) -> String {
+    if term.mutable {
+        term.ident.clone()
+    } else {
+        format!("const {}", term.ident)
+    }
+}
+
+// `Transform&, const Velocity&`
+fn query_args(terms: &[SystemInputInfo]) -> String {
+    let args: Vec<_> = terms.iter().map(|t| format!("{}&", term_type(t))).collect();
+    args.join(", ")
+}
+
+fn gen_query(terms: &[SystemInputInfo]) -> String {
+    let mut output = String::new();
+
+    let args = query_args(terms);
+
+    // the components of one matching entity, as the callbacks hand them over
+    let components: Vec<_> = terms
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("*static_cast<{}*>(components[{i}])", term_type(t)))
+        .collect();
+
+    let derefs: Vec<_> = (0..terms.len()).map(|i| format!("*term_{i}")).collect();
+
+    output += "template <>\n";
+    output += &format!("struct TypedQuery<{args}> {{\n");
+    output += &format!("    using Item = std::tuple<{args}>;\n\n");
+    output += "    void* handle;\n\n";
+    output +=
+        &format!("    explicit TypedQuery(Query<{args}> query) : handle(query.handle) {{}}\n\n");
+
+    output += "    template <typename F>\n";
+    output += "    void for_each(F f) {\n";
+    output += "        QueryForEachCallback callback = [](void* user_data, void** components) {\n";
+    output += "            (*static_cast<F*>(user_data))(\n";
+    output += &format!(
+        "                {}\n",
+        components.join(",\n                ")
+    );
+    output += "            );\n";
+    output += "        };\n\n";
+    output += "        QueryForEachFn(handle, callback, &f);\n";
+    output += "    }\n\n";
+
+    output += "    template <typename F>\n";
+    output += "    void par_for_each(const F& f) {\n";
+    output += "        QueryParForEachCallback callback = [](const void* user_data, void** components) {\n";
+    output += "            (*static_cast<const F*>(user_data))(\n";
+    output += &format!(
+        "                {}\n",
+        components.join(",\n                ")
+    );
+    output += "            );\n";
+    output += "        };\n\n";
+    output += "        QueryParForEachFn(handle, callback, &f);\n";
+    output += "    }\n\n";
+
+    // `entity` has to match the whole query, not just have some of the components
+    output += "    std::optional<Item> get(EntityId entity) {\n";
+    for (i, term) in terms.iter().enumerate() {
+        let get = if term.mutable {
+            "QueryGetMutFn"
+        } else {
+            "QueryGetFn"
+        };
+        output += &format!(
+            "        {0}* term_{i} = static_cast<{0}*>({get}(handle, entity, Component<{1}>::ID));\n",
+            term_type(term),
+            term.ident
+        );
+    }
+    output += "\n";
+    let missing: Vec<_> = (0..terms.len())
+        .map(|i| format!("term_{i} == nullptr"))
+        .collect();
+    output += &format!("        if ({}) {{\n", missing.join(" || "));
+    output += "            return std::nullopt;\n";
+    output += "        }\n\n";
+    output += &format!("        return Item({});\n", derefs.join(", "));
+    output += "    }\n\n";
+
+    // engines from before the counting callbacks leave them null, so fall back to iterating
+    output += "    size_t len() const {\n";
+    output += "        if (QueryLenFn == nullptr) {\n";
+    output += "            return count(nullptr);\n";
+    output += "        }\n\n";
+    output += "        return QueryLenFn(handle);\n";
+    output += "    }\n\n";
+
+    output += "    bool is_empty() const {\n";
+    output += "        if (QueryIsEmptyFn == nullptr) {\n";
+    output += "            return count(nullptr) == 0;\n";
+    output += "        }\n\n";
+    output += "        return QueryIsEmptyFn(handle);\n";
+    output += "    }\n\n";
+
+    // empty unless exactly one entity matches, rather than picking one of several, and reported
+    // to the engine with the number of matches
+    output += "    std::optional<Item> single() {\n";
+    output += &format!("        void* components[{}] = {{}};\n", terms.len());
+    output += "        size_t matches = QuerySingleFn != nullptr\n";
+    output += "            ? QuerySingleFn(handle, components)\n";
+    output += "            : count(components);\n\n";
+    output += "        if (matches != 1) {\n";
+    output += "            if (ReportErrorFn != nullptr) {\n";
+    output += "                char message[64];\n";
+    output += "                std::snprintf(message, sizeof(message), \"expected one match, found %zu\", matches);\n";
+    output += "                ReportErrorFn(\"TypedQuery::single\", message);\n";
+    output += "            }\n\n";
+    output += "            return std::nullopt;\n";
+    output += "        }\n\n";
+    output += "        return Item(\n";
+    output += &format!("            {}\n", components.join(",\n            "));
+    output += "        );\n";
+    output += "    }\n\n";
+
+    // Counts matches through for_each, which can't stop early, so every match is visited even
+    // when only emptiness is asked. The components of the first match are copied to `first` when
+    // given.
+    output += "private:\n";
+    output += "    size_t count(void** first) const {\n";
+    output += "        struct Counter {\n";
+    output += "            size_t matches;\n";
+    output += "            void** first;\n";
+    output += "        };\n\n";
+    output += "        QueryForEachCallback callback = [](void* user_data, void** components) {\n";
+    output += "            Counter* counter = static_cast<Counter*>(user_data);\n";
+    output += "            if (counter->matches == 0 && counter->first != nullptr) {\n";
+    output += &format!(
+        "                for (size_t i = 0; i < {}; ++i) {{\n",
+        terms.len()
+    );
+    output += "                    counter->first[i] = components[i];\n";
+    output += "                }\n";
+    output += "            }\n\n";
+    output += "            ++counter->matches;\n";
+    output += "        };\n\n";
+    output += "        Counter counter = { 0, first };\n";
+    output += "        QueryForEachFn(handle, callback, &counter);\n";
+    output += "        return counter.matches;\n";
+    output += "    }\n";
+
+    output += "};\n\n";
+
+    output
+}
@@ -0,0 +1,543 @@
+use crate::abi::{abi_types, CALLBACKS, ENGINE_TYPES};
+use crate::cpp::callback_description;
+use crate::{FfiGenerator, PublicStructInfo, StructInfo, StructType};
+
+impl FfiGenerator {
+    // Rust mirror of the C header for the engine's module loader. The types and signatures are
+    // translated from the same declarations, so an ABI change breaks the engine build.
+    pub(crate) fn gen_rust_bindings(&self) -> String {
+        let mut output = String::new();
+
+        output += "use std::ffi::{c_char, c_void, CStr};\n\n";
+
+        for abi_type in ENGINE_TYPES.iter().chain(&abi_types()) {
+            output += &abi_type.gen_rust();
+            output += "\n";
+        }
+
+        // every callback starts out null, for the engine to fill in those it provides
+        output += "impl EngineCallbacks {\n";
+        output += "    pub fn new() -> Self {\n";
+        output += "        Self {\n";
+        output += "            size: std::mem::size_of::<Self>() as u32,\n";
+        output += "            version: ENGINE_CALLBACKS_VERSION,\n";
+        for callback in CALLBACKS {
+            output += &format!("            {}: None,\n", rust_ident(callback.field));
+        }
+        output += "        }\n";
+        output += "    }\n";
+        output += "}\n\n";
+
+        output += "impl Default for EngineCallbacks {\n";
+        output += "    fn default() -> Self {\n";
+        output += "        Self::new()\n";
+        output += "    }\n";
+        output += "}\n\n";
+
+        let signatures = self.exports();
+        let exports: Vec<_> = signatures.iter().map(|e| parse_export(e)).collect();
+
+        output += "#[derive(Debug, Clone, Copy)]\n";
+        output += "pub struct MissingSymbol(pub &'static CStr);\n\n";
+
+        output += "impl std::fmt::Display for MissingSymbol {\n";
+        output += "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n";
+        output += "        write!(f, \"game module is missing the symbol {:?}\", self.0)\n";
+        output += "    }\n";
+        output += "}\n\n";
+
+        output += "impl std::error::Error for MissingSymbol {}\n\n";
+
+        output += "unsafe fn symbol<T: Copy>(\n";
+        output += "    lookup: &mut impl FnMut(&CStr) -> *const c_void,\n";
+        output += "    name: &'static CStr,\n";
+        output += ") -> Result<T, MissingSymbol> {\n";
+        output += "    let ptr = lookup(name);\n";
+        output += "    if ptr.is_null() {\n";
+        output += "        return Err(MissingSymbol(name));\n";
+        output += "    }\n\n";
+        output += "    Ok(std::mem::transmute_copy(&ptr))\n";
+        output += "}\n\n";
+
+        output += "/// Every function exported by a game module, resolved up front.\n";
+        output += "pub struct ModuleApi {\n";
+        for export in &exports {
+            output += &format!(
+                "    {}: {},\n",
+                export.ident,
+                rust_fn_ptr_from_params(export.ret, &export.params)
+            );
+        }
+        output += "}\n\n";
+
+        output += "impl ModuleApi {\n";
+        output += "    /// # Safety\n";
+        output += "    ///\n";
+        output += "    /// `lookup` must resolve symbols of a game module generated by arete-codegen, and the\n";
+        output += "    /// module must stay loaded for as long as the returned value is used.\n";
+        output += "    pub unsafe fn load(\n";
+        output += "        mut lookup: impl FnMut(&CStr) -> *const c_void,\n";
+        output += "    ) -> Result<Self, MissingSymbol> {\n";
+        output += "        Ok(Self {\n";
+        for export in &exports {
+            output += &format!(
+                "            {0}: symbol(&mut lookup, c\"{0}\")?,\n",
+                export.ident
+            );
+        }
+        output += "        })\n";
+        output += "    }\n";
+
+        for export in &exports {
+            output += "\n";
+            output += &export.gen_rust_method();
+        }
+
+        output += "}\n";
+
+        output += &self.gen_rust_abi_hash();
+        output += &self.gen_rust_components();
+
+        output
+    }
+
+    // Mirrors `abi_description` over a loaded module's descriptor, with the engine's callback
+    // signatures baked in when the bindings are generated.
+    fn gen_rust_abi_hash(&self) -> String {
+        let mut output = String::new();
+
+        output += "\n";
+        if let Some(hash) = self.expected_abi_hash() {
+            output += "/// What `arete_abi_hash` returns for the module these bindings were generated from.\n";
+            output += &format!("pub const ARETE_ABI_HASH: u64 = 0x{hash:016x};\n\n");
+        }
+
+        output += &format!(
+            "const ABI_CALLBACKS: &str = {:?};\n\n",
+            callback_description()
+        );
+
+        output += "/// What `arete_abi_hash` should return for a module with this descriptor, given the string id,\n";
+        output += "/// size and alignment of each type in the engine catalogue.\n";
+        output += "///\n";
+        output += "/// # Safety\n";
+        output += "///\n";
+        output += "/// `module` must be the descriptor returned by the module's `arete_module_descriptor`.\n";
+        output += "pub unsafe fn expected_abi_hash(module: *const ModuleDescriptor, public: &[(&str, usize, usize)]) -> u64 {\n";
+        output += "    let string = |ptr: *const c_char| CStr::from_ptr(ptr).to_string_lossy();\n";
+        output += "    let access = |arg_type: ArgType| match arg_type {\n";
+        output += "        ArgType::DataAccessMut => \"mut\",\n";
+        output += "        ArgType::DataAccessRef => \"ref\",\n";
+        output += "        ArgType::Query => \"query\",\n";
+        output += "        _ => \"unknown\",\n";
+        output += "    };\n\n";
+        output += "    let module = &*module;\n";
+        output += "    let mut description = String::new();\n";
+        output += "    let mut inputs = std::collections::BTreeSet::new();\n\n";
+        output += "    for i in 0..module.components_len {\n";
+        output += "        let component = &*module.components.add(i);\n";
+        output += "        let kind = match component.component_type {\n";
+        output += "            ComponentType::Component => \"component\",\n";
+        output += "            ComponentType::Resource => \"resource\",\n";
+        output += "            _ => \"unknown\",\n";
+        output += "        };\n";
+        output += "        description += &format!(\"component {} {kind}\\n\", string(component.string_id));\n";
+        output += "    }\n\n";
+        output += "    for i in 0..module.systems_len {\n";
+        output += "        let system = &*module.systems.add(i);\n";
+        output += "        description += if system.is_once { \"system once\\n\" } else { \"system always\\n\" };\n\n";
+        output += "        for j in 0..system.args_len {\n";
+        output += "            let arg = &*system.args.add(j);\n";
+        output += "            if arg.arg_type != ArgType::Query {\n";
+        output += "                description += &format!(\"arg {} {}\\n\", access(arg.arg_type), string(arg.component));\n";
+        output += "                inputs.insert(string(arg.component));\n";
+        output += "                continue;\n";
+        output += "            }\n\n";
+        output += "            description += \"arg query\\n\";\n";
+        output += "            for k in 0..arg.query_terms_len {\n";
+        output += "                let term = &*arg.query_terms.add(k);\n";
+        output += "                description += &format!(\"term {} {}\\n\", access(term.arg_type), string(term.component));\n";
+        output += "                inputs.insert(string(term.component));\n";
+        output += "            }\n";
+        output += "        }\n";
+        output += "    }\n\n";
+        output += "    description += ABI_CALLBACKS;\n\n";
+        output += "    // 64-bit FNV-1a, continued over the layouts of the engine types the systems take, in string\n";
+        output += "    // id order, as little-endian `uint64_t`s\n";
+        output += "    let layouts = inputs\n";
+        output += "        .iter()\n";
+        output += "        .filter_map(|input| public.iter().find(|(string_id, ..)| string_id == input))\n";
+        output += "        .flat_map(|&(_, size, align)| [size as u64, align as u64])\n";
+        output += "        .flat_map(u64::to_le_bytes);\n\n";
+        output += "    let mut hash = 0xcbf29ce484222325u64;\n";
+        output += "    for b in description.bytes().chain(layouts) {\n";
+        output += "        hash ^= b as u64;\n";
+        output += "        hash = hash.wrapping_mul(0x100000001b3);\n";
+        output += "    }\n\n";
+        output += "    hash\n";
+        output += "}\n";
+
+        output
+    }
+
+    // Only components made entirely of primitives and public types with a known layout can be
+    // mirrored. Each mirror asserts the layout worked out from the parsed fields when it doesn't
+    // depend on the target, so a mirror can't silently go stale.
+    fn gen_rust_components(&self) -> String {
+        let mirrors: Vec<_> = self
+            .structs
+            .iter()
+            .filter_map(|s| self.rust_mirror(s))
+            .collect();
+
+        if mirrors.is_empty() {
+            return String::new();
+        }
+
+        let mut public_types: Vec<_> = mirrors
+            .iter()
+            .flat_map(|m| &m.fields)
+            .filter_map(|f| f.public)
+            .collect();
+        public_types.sort_unstable_by(|a, b| a.ident.cmp(&b.ident));
+        public_types.dedup_by(|a, b| a.ident == b.ident);
+
+        let mut items = Vec::new();
+
+        for public in public_types {
+            let (size, align) = (public.size.unwrap(), public.align.unwrap());
+            let mut item = String::new();
+
+            item += &format!(
+                "    /// Opaque mirror of the engine's `{}`.\n",
+                public.ident
+            );
+            item += &format!("    #[repr(C, align({align}))]\n");
+            item += "    #[derive(Debug, Clone, Copy)]\n";
+            item += &format!("    pub struct {}(pub [u8; {size}]);\n", public.ident);
+
+            items.push(item);
+        }
+
+        for mirror in &mirrors {
+            let mut item = String::new();
+
+            item += "    #[repr(C)]\n";
+            item += "    #[derive(Debug, Clone, Copy)]\n";
+            item += &format!("    pub struct {} {{\n", mirror.ident);
+            for field in &mirror.fields {
+                item += &format!("        pub {}: {},\n", field.ident, field.ty);
+            }
+            item += "    }\n";
+
+            if let Some((size, align)) = mirror.layout {
+                item += "\n";
+                item += &format!(
+                    "    const _: () = assert!(std::mem::size_of::<{}>() == {size});\n",
+                    mirror.ident
+                );
+                item += &format!(
+                    "    const _: () = assert!(std::mem::align_of::<{}>() == {align});\n",
+                    mirror.ident
+                );
+            }
+
+            items.push(item);
+        }
+
+        let mut output = String::new();
+
+        output += "\npub mod components {\n";
+        output += &items.join("\n");
+        output += "}\n";
+
+        output
+    }
+
+    fn rust_mirror<'a>(&'a self, struct_info: &'a StructInfo) -> Option<RustMirror<'a>> {
+        if struct_info.struct_type != StructType::Component
+            || struct_info.fields.is_empty()
+            || struct_info.has_skipped_members
+        {
+            return None;
+        }
+
+        let mut fields = Vec::new();
+        let mut size = 0usize;
+        let mut align = Some(1);
+
+        for field in &struct_info.fields {
+            let (mut ty, field_size, field_align, public) =
+                if let Some((ty, size)) = primitive_layout(&field.ty) {
+                    // 8 byte types are only 4 byte aligned in structs on i386
+                    (ty.to_owned(), size, (size <= 4).then_some(size), None)
+                } else {
+                    let public = self.catalogue.find(&field.ty)?;
+                    (
+                        public.ident.clone(),
+                        public.size?,
+                        Some(public.align?),
+                        Some(public),
+                    )
+                };
+
+            let mut field_size = field_size;
+            if let Some(len) = &field.array_len {
+                let len: usize = len.parse().ok()?;
+                ty = format!("[{ty}; {len}]");
+                field_size *= len;
+            }
+
+            size = size.next_multiple_of(field_align.unwrap_or(1)) + field_size;
+            align = align.zip(field_align).map(|(a, b)| a.max(b));
+
+            fields.push(RustMirrorField {
+                ident: rust_ident(&field.ident),
+                ty,
+                public,
+            });
+        }
+
+        Some(RustMirror {
+            ident: &struct_info.ident,
+            fields,
+            layout: align.map(|align| (size.next_multiple_of(align), align)),
+        })
+    }
+}
+
+struct RustMirror<'a> {
+    ident: &'a str,
+    fields: Vec<RustMirrorField<'a>>,
+    // size and alignment, unless they depend on the target
+    layout: Option<(usize, usize)>,
+}
+
+struct RustMirrorField<'a> {
+    ident: String,
+    ty: String,
+    public: Option<&'a PublicStructInfo>,
+}
+
+// the Rust type and size of C++ types whose size doesn't depend on the platform
+fn primitive_layout(ty: &str) -> Option<(&'static str, usize)> {
+    Some(match ty {
+        "bool" => ("bool", 1),
+        // plain `char` is unsigned on some platforms
+        "char" => ("core::ffi::c_char", 1),
+        "int8_t" | "signed char" => ("i8", 1),
+        "uint8_t" | "unsigned char" => ("u8", 1),
+        "int16_t" | "short" => ("i16", 2),
+        "uint16_t" | "unsigned short" => ("u16", 2),
+        "int32_t" | "int" => ("i32", 4),
+        "uint32_t" | "unsigned" | "unsigned int" => ("u32", 4),
+        "int64_t" | "long long" => ("i64", 8),
+        "uint64_t" | "unsigned long long" => ("u64", 8),
+        "float" => ("f32", 4),
+        "double" => ("f64", 8),
+        "EntityId" => ("super::EntityId", 8),
+        _ => return None,
+    })
+}
+
+pub(crate) fn rust_ident(ident: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "box", "crate", "dyn", "fn", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "type", "unsafe", "use", "where",
+    ];
+
+    if KEYWORDS.contains(&ident) {
+        format!("r#{ident}")
+    } else {
+        ident.to_owned()
+    }
+}
+
+struct ExportInfo<'a> {
+    ident: &'a str,
+    ret: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+}
+
+fn parse_export(signature: &str) -> ExportInfo<'_> {
+    let (head, params) = signature.split_once('(').unwrap();
+    let params = params.trim_end().trim_end_matches(')');
+    let (ret, ident) = split_c_declaration(head);
+
+    ExportInfo {
+        ident,
+        ret,
+        params: params
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty() && *p != "void")
+            .map(split_c_declaration)
+            .collect(),
+    }
+}
+
+// splits `const char* string_id` into `const char*` and `string_id`
+fn split_c_declaration(declaration: &str) -> (&str, &str) {
+    let declaration = declaration.trim();
+    let name_start = declaration
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    (
+        declaration[..name_start].trim_end(),
+        &declaration[name_start..],
+    )
+}
+
+impl ExportInfo<'_> {
+    // Strings cross as `&CStr`. Anything else passed by pointer can't be checked here, so those
+    // methods stay unsafe.
+    fn gen_rust_method(&self) -> String {
+        let mut output = String::new();
+
+        let is_unsafe = self
+            .params
+            .iter()
+            .any(|(ty, _)| ty.ends_with('*') && *ty != "const char*");
+
+        let params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(ty, ident)| match *ty {
+                "const char*" => format!("{ident}: &CStr"),
+                _ => format!("{ident}: {}", rust_type(ty)),
+            })
+            .collect();
+
+        let args: Vec<_> = self
+            .params
+            .iter()
+            .map(|(ty, ident)| match *ty {
+                "const char*" => format!("{ident}.as_ptr()"),
+                _ => ident.to_string(),
+            })
+            .collect();
+
+        let call = format!("(self.{})({})", self.ident, args.join(", "));
+
+        let (ret, body) = match self.ret {
+            "void" => (String::new(), call),
+            "const char*" => (
+                " -> Option<&CStr>".to_owned(),
+                format!("let ptr = {call};\n        (!ptr.is_null()).then(|| CStr::from_ptr(ptr))"),
+            ),
+            ret => (format!(" -> {}", rust_type(ret)), call),
+        };
+
+        if is_unsafe {
+            output += "    /// # Safety\n";
+            output += "    ///\n";
+            output += "    /// Pointer arguments must be valid for the generated implementation to access.\n";
+            output += &format!(
+                "    pub unsafe fn {}(&self, {}){ret} {{\n",
+                self.ident,
+                params.join(", ")
+            );
+            output += &format!("        {body}\n");
+        } else {
+            output += &format!(
+                "    pub fn {}(&self{}){ret} {{\n",
+                self.ident,
+                params.iter().map(|p| format!(", {p}")).collect::<String>()
+            );
+            if body.contains('\n') {
+                output += "        unsafe {\n";
+                output += &format!(
+                    "            {}\n",
+                    body.replace("\n        ", "\n            ")
+                );
+                output += "        }\n";
+            } else {
+                output += &format!("        unsafe {{ {body} }}\n");
+            }
+        }
+
+        output += "    }\n";
+
+        output
+    }
+}
+
+pub(crate) fn rust_type(c_ty: &str) -> String {
+    let c_ty = c_ty.trim();
+
+    if let Some(pointee) = c_ty.strip_suffix('*') {
+        let pointee = pointee.trim_end();
+
+        // in `const char**` the const belongs to the innermost pointee
+        if pointee.ends_with('*') {
+            return format!("*mut {}", rust_type(pointee));
+        }
+
+        let (mutability, pointee) = match pointee.strip_prefix("const ") {
+            Some(pointee) => ("const", pointee),
+            None => ("mut", pointee),
+        };
+
+        let pointee = match pointee {
+            "void" => String::from("c_void"),
+            "char" => String::from("c_char"),
+            pointee => rust_type(pointee),
+        };
+
+        return format!("*{mutability} {pointee}");
+    }
+
+    match c_ty {
+        "void" => String::from("()"),
+        "bool" => String::from("bool"),
+        "uint8_t" => String::from("u8"),
+        "int32_t" => String::from("i32"),
+        "uint32_t" => String::from("u32"),
+        "uint64_t" => String::from("u64"),
+        "size_t" => String::from("usize"),
+        ident => camel_case(ident),
+    }
+}
+
+pub(crate) fn rust_fn_ptr(ret: &str, args: &str) -> String {
+    let args: Vec<_> = args
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty() && *a != "void")
+        .map(|a| (a, ""))
+        .collect();
+
+    rust_fn_ptr_from_params(ret, &args)
+}
+
+fn rust_fn_ptr_from_params(ret: &str, params: &[(&str, &str)]) -> String {
+    let args: Vec<_> = params.iter().map(|(ty, _)| rust_type(ty)).collect();
+
+    let mut output = format!("unsafe extern \"C\" fn({})", args.join(", "));
+    if ret != "void" {
+        output += " -> ";
+        output += &rust_type(ret);
+    }
+
+    output
+}
+
+// `system_fn_ptr` -> `SystemFnPtr`, names which are already camel case are kept
+pub(crate) fn camel_case(ident: &str) -> String {
+    if !ident.contains('_') {
+        return ident.to_owned();
+    }
+
+    ident
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
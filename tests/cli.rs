@@ -0,0 +1,172 @@
+// Runs the arete-codegen binary on modules written to a scratch directory.
+
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("cli-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_arete-codegen"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn diff_source_trees() {
+    let dir = scratch_dir("diff");
+
+    // the module is split over several headers, one of them nested
+    for (tree, health) in [("old", "float"), ("new", "double")] {
+        let components = dir.join(tree).join("components");
+        fs::create_dir_all(&components).unwrap();
+
+        fs::write(
+            components.join("health.h"),
+            format!("struct Health {{ {health} value; }};\nCOMPONENT(Health);\n"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join(tree).join("systems.h"),
+            "SYSTEM(void regenerate, Query<Health&> query);\n",
+        )
+        .unwrap();
+    }
+
+    let old = dir.join("old");
+    let output = run(&["diff", old.to_str().unwrap(), old.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let new = dir.join("new");
+    let output = run(&["diff", old.to_str().unwrap(), new.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "breaking: component Health changed its fields\n"
+    );
+
+    let missing = dir.join("missing");
+    let output = run(&["diff", old.to_str().unwrap(), missing.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn depfile_lists_every_output() {
+    let dir = scratch_dir("depfile");
+    let module = dir.join("my game");
+    fs::create_dir_all(&module).unwrap();
+
+    fs::write(
+        module.join("game.h"),
+        "struct Health { float value; };\nCOMPONENT(Health);\n",
+    )
+    .unwrap();
+
+    let path = |name: &str| module.join(name).to_str().unwrap().to_owned();
+    let output = run(&[
+        "--input",
+        &path("game.h"),
+        "--header",
+        &path("ffi.h"),
+        "--manifest",
+        &path("manifest.json"),
+        "--rust-bindings",
+        &path("bindings.rs"),
+        "--queries",
+        &path("queries.h"),
+        "--test-harness",
+        &path("harness.h"),
+        "--depfile",
+        &path("ffi.d"),
+    ]);
+    assert!(output.status.success());
+
+    let escaped = |name: &str| path(name).replace(' ', "\\ ");
+    let targets = [
+        "ffi.cpp",
+        "ffi.h",
+        "manifest.json",
+        "bindings.rs",
+        "queries.h",
+        "harness.h",
+    ];
+    let targets: Vec<_> = targets.iter().map(|name| escaped(name)).collect();
+
+    assert_eq!(
+        fs::read_to_string(module.join("ffi.d")).unwrap(),
+        format!("{}: \\\n    {}\n", targets.join(" "), escaped("game.h"))
+    );
+}
+
+#[test]
+fn check_in_another_checkout() {
+    let dir = scratch_dir("checkout");
+
+    for checkout in ["a", "b"] {
+        fs::create_dir_all(dir.join(checkout)).unwrap();
+        fs::write(
+            dir.join(checkout).join("game.h"),
+            "struct Health { float value; };\nCOMPONENT(Health);\n",
+        )
+        .unwrap();
+    }
+
+    let input = dir.join("a/game.h");
+    let output = run(&["--input", input.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    // generated files are checked in, so they can't depend on where they were generated
+    for entry in fs::read_dir(dir.join("a")).unwrap() {
+        let path = entry.unwrap().path();
+        fs::copy(&path, dir.join("b").join(path.file_name().unwrap())).unwrap();
+    }
+
+    let input = dir.join("b/game.h");
+    let output = run(&["--input", input.to_str().unwrap(), "--check"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn header_is_opt_in() {
+    let dir = scratch_dir("header");
+
+    // a header the build already has, which generating ffi.cpp mustn't replace
+    fs::write(
+        dir.join("game.h"),
+        "struct Health { float value; };\nCOMPONENT(Health);\n",
+    )
+    .unwrap();
+    fs::write(dir.join("ffi.h"), "// written by hand\n").unwrap();
+
+    let input = dir.join("game.h");
+    let output = run(&["--input", input.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    assert!(dir.join("ffi.cpp").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join("ffi.h")).unwrap(),
+        "// written by hand\n"
+    );
+
+    let harness = dir.join("harness.h");
+    let output = run(&[
+        "--input",
+        input.to_str().unwrap(),
+        "--test-harness",
+        harness.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success(), "the harness needs the C header");
+}
@@ -0,0 +1,22 @@
+// Shared by the tests that build generated code, including the host's through a `#[path]` module.
+
+use std::env;
+use std::process::Command;
+
+// `$CXX`, or `c++` when unset. Tests skip when it can't be run, unless `ARETE_REQUIRE_CXX` is set so
+// CI can't pass without building anything.
+pub fn compiler() -> Option<String> {
+    let cxx = env::var("CXX").unwrap_or_else(|_| String::from("c++"));
+
+    if Command::new(&cxx).arg("--version").output().is_ok() {
+        return Some(cxx);
+    }
+
+    assert!(
+        env::var_os("ARETE_REQUIRE_CXX").is_none(),
+        "no C++ compiler found at {cxx}"
+    );
+
+    eprintln!("skipping, no C++ compiler found at {cxx}");
+    None
+}
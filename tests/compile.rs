@@ -0,0 +1,346 @@
+// Compiles and links the generated code for each valid fixture against mock/arete.h, using `$CXX`
+// (or `c++`), then runs a small driver through the generated C header. Skipped when no compiler is
+// found, unless `ARETE_REQUIRE_CXX` is set as it should be in CI.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use arete_codegen::{Backend, CppOptions, EngineCatalogue};
+
+mod common;
+
+const DRIVER: &str = r#"#include "ffi.h"
+
+#include <cstdio>
+#include <cstring>
+
+int main() {
+    const ModuleDescriptor* module = arete_module_descriptor();
+
+    if (module->engine_version != arete_target_version()) {
+        return 1;
+    }
+
+    // every string id has to find its way back to its own descriptor through the lookup
+    for (size_t i = 0; i < module->components_len; ++i) {
+        const ComponentDescriptor* component = &module->components[i];
+        if (component_size(component->string_id) != component->size ||
+            component_align(component->string_id) != component->align) {
+            std::fprintf(stderr, "%s doesn't map back to its descriptor\n", component->string_id);
+            return 1;
+        }
+    }
+
+    for (size_t i = 0; i < module->systems_len; ++i) {
+        if (std::strcmp(system_name(i), module->systems[i].name) != 0 ||
+            system_source_location(i).line == 0) {
+            std::fprintf(stderr, "system %zu has no name or source location\n", i);
+            return 1;
+        }
+
+        // zero is success, whatever the system's error enum calls it
+        if (system_error_name(i, 0) != nullptr) {
+            std::fprintf(stderr, "system %zu names its success code\n", i);
+            return 1;
+        }
+    }
+
+    // a mismatched engine gets an error back instead of an abort
+    size_t size = 0;
+    if (try_component_size("unknown::Component", &size) != ModuleStatusUnknownComponent ||
+        module_last_error() == nullptr) {
+        std::fprintf(stderr, "an unknown string id isn't reported\n");
+        return 1;
+    }
+
+    int32_t code = 0;
+    size_t entity_fields_len = 0;
+    const EntityField* entity_fields = nullptr;
+    if (try_resource_init("unknown::Resource", nullptr, &code) != ModuleStatusUnknownComponent ||
+        module_last_error() == nullptr ||
+        try_component_entity_fields("unknown::Component", &entity_fields_len, &entity_fields) !=
+            ModuleStatusUnknownComponent ||
+        module_last_error() == nullptr ||
+        try_component_remap_entities("unknown::Component", nullptr, nullptr) !=
+            ModuleStatusUnknownComponent ||
+        module_last_error() == nullptr) {
+        std::fprintf(stderr, "an unknown string id is ignored\n");
+        return 1;
+    }
+
+    bool is_once = false;
+    if (try_system_is_once(module->systems_len, &is_once) != ModuleStatusUnknownSystem) {
+        std::fprintf(stderr, "an unknown system index isn't reported\n");
+        return 1;
+    }
+
+    // and a later success doesn't leave the old error behind
+    if (module->systems_len > 0 &&
+        (try_system_is_once(0, &is_once) != ModuleStatusOk || module_last_error() != nullptr)) {
+        std::fprintf(stderr, "a successful call doesn't clear the last error\n");
+        return 1;
+    }
+
+    std::printf("%016llx\n", (unsigned long long)arete_abi_hash());
+    return 0;
+}
+"#;
+
+// prints the layout the module reports for each of its components
+const LAYOUTS: &str = r#"#include "ffi.h"
+
+#include <cstdio>
+
+int main() {
+    const ModuleDescriptor* module = arete_module_descriptor();
+
+    for (size_t i = 0; i < module->components_len; ++i) {
+        const ComponentDescriptor* component = &module->components[i];
+        std::printf("%s %zu %zu\n", component->string_id, component->size, component->align);
+    }
+
+    return 0;
+}
+"#;
+
+// the same for the Rust mirrors of the components fixture
+const MIRRORS: &str = r#"#![allow(dead_code)]
+
+include!("bindings.rs");
+
+fn main() {
+    let layouts = [
+        (
+            "game_module::Velocity",
+            std::mem::size_of::<components::Velocity>(),
+            std::mem::align_of::<components::Velocity>(),
+        ),
+        (
+            "game_module::Tint",
+            std::mem::size_of::<components::Tint>(),
+            std::mem::align_of::<components::Tint>(),
+        ),
+    ];
+
+    for (string_id, size, align) in layouts {
+        println!("{string_id} {size} {align}");
+    }
+
+    // statuses from a newer module are still valid values
+    assert_eq!(format!("{:?}", ModuleStatus::MissingCallback), "MissingCallback");
+    assert_eq!(format!("{:?}", ModuleStatus(42)), "ModuleStatus(42)");
+}
+"#;
+
+fn compile_fixture(name: &str) {
+    compile(name, false);
+}
+
+fn compile(name: &str, no_exceptions: bool) {
+    let Some(cxx) = common::compiler() else {
+        return;
+    };
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixture = root.join("tests/fixtures").join(name);
+    let catalogue = EngineCatalogue::load(&root.join("mock/engine.json")).unwrap();
+
+    let source = fs::read_to_string(fixture.join("game.h")).unwrap();
+    let module = arete_codegen::parse_source(&source, "game.h", catalogue).unwrap();
+
+    let variant = if no_exceptions { "-no-exceptions" } else { "" };
+    let out_dir =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("compile-{name}{variant}"));
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let ffi = module.generate(&Backend::Cpp {
+        include: String::from("game.h"),
+        options: CppOptions {
+            no_exceptions,
+            ..CppOptions::default()
+        },
+    });
+
+    fs::write(out_dir.join("ffi.cpp"), ffi.unwrap()).unwrap();
+    fs::write(
+        out_dir.join("ffi.h"),
+        module.generate(&Backend::CHeader).unwrap(),
+    )
+    .unwrap();
+    fs::write(out_dir.join("main.cpp"), DRIVER).unwrap();
+
+    // the typed queries have to build after the systems, which the mock defines inline
+    let queries = module.generate(&Backend::Queries {
+        include: String::from("game.h"),
+    });
+    fs::write(out_dir.join("queries.h"), queries.unwrap()).unwrap();
+    fs::write(out_dir.join("queries.cpp"), "#include \"queries.h\"\n").unwrap();
+
+    let exe = out_dir.join("module");
+
+    let output = Command::new(&cxx)
+        .args([
+            "-std=c++20",
+            "-Wall",
+            "-Werror",
+            "-DARETE_MOCK_DEFINE_SYSTEMS",
+        ])
+        .args(no_exceptions.then_some("-fno-exceptions"))
+        .arg("-I")
+        .arg(&fixture)
+        .arg("-I")
+        .arg(root.join("mock"))
+        .arg("-I")
+        .arg(&out_dir)
+        .arg(out_dir.join("ffi.cpp"))
+        .arg(out_dir.join("main.cpp"))
+        .arg(out_dir.join("queries.cpp"))
+        .arg("-o")
+        .arg(&exe)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "generated code for fixture {name} failed to build:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&exe).output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "module for fixture {name} failed its checks:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn components() {
+    compile_fixture("components");
+}
+
+#[test]
+fn resources() {
+    compile_fixture("resources");
+}
+
+#[test]
+fn once_systems() {
+    compile_fixture("once_systems");
+}
+
+#[test]
+fn queries() {
+    compile_fixture("queries");
+}
+
+#[test]
+fn errors() {
+    compile_fixture("errors");
+}
+
+#[test]
+fn empty() {
+    compile_fixture("empty");
+}
+
+#[test]
+fn without_exceptions() {
+    compile("errors", true);
+}
+
+// Built against the mock engine catalogue, so fields of public types can be mirrored too.
+#[test]
+fn rust_mirrors() {
+    let Some(cxx) = common::compiler() else {
+        return;
+    };
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixture = root.join("tests/fixtures/components");
+    let catalogue = EngineCatalogue::load(&root.join("mock/engine.json")).unwrap();
+
+    let source = fs::read_to_string(fixture.join("game.h")).unwrap();
+    let module = arete_codegen::parse_source(&source, "game.h", catalogue).unwrap();
+
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("rust-mirrors");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let ffi = module
+        .generate(&Backend::Cpp {
+            include: String::from("game.h"),
+            options: CppOptions::default(),
+        })
+        .unwrap();
+
+    fs::write(out_dir.join("ffi.cpp"), ffi).unwrap();
+    fs::write(
+        out_dir.join("ffi.h"),
+        module.generate(&Backend::CHeader).unwrap(),
+    )
+    .unwrap();
+    fs::write(out_dir.join("layouts.cpp"), LAYOUTS).unwrap();
+    let bindings = module.generate(&Backend::RustBindings).unwrap();
+    // the parser skipped some of Packet's members, so its fields don't give its layout
+    assert!(!bindings.contains("pub struct Packet"));
+
+    fs::write(out_dir.join("bindings.rs"), bindings).unwrap();
+    fs::write(out_dir.join("mirrors.rs"), MIRRORS).unwrap();
+
+    let layouts = out_dir.join("layouts");
+    run_checked(
+        Command::new(&cxx)
+            .args([
+                "-std=c++20",
+                "-Wall",
+                "-Werror",
+                "-DARETE_MOCK_DEFINE_SYSTEMS",
+            ])
+            .arg("-I")
+            .arg(&fixture)
+            .arg("-I")
+            .arg(root.join("mock"))
+            .arg("-I")
+            .arg(&out_dir)
+            .arg(out_dir.join("ffi.cpp"))
+            .arg(out_dir.join("layouts.cpp"))
+            .arg("-o")
+            .arg(&layouts),
+    );
+
+    // the mirrors assert their own layouts, so building them is most of the check
+    let mirrors = out_dir.join("mirrors");
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    run_checked(
+        Command::new(rustc)
+            .args(["--edition", "2021"])
+            .arg(out_dir.join("mirrors.rs"))
+            .arg("-o")
+            .arg(&mirrors),
+    );
+
+    let cpp_layouts = run_checked(&mut Command::new(&layouts));
+    let rust_layouts = run_checked(&mut Command::new(&mirrors));
+
+    for line in rust_layouts.lines() {
+        assert!(
+            cpp_layouts.lines().any(|cpp| cpp == line),
+            "mirror layout {line} doesn't match the module's:\n{cpp_layouts}"
+        );
+    }
+}
+
+fn run_checked(command: &mut Command) -> String {
+    let output = command.output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "{command:?} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
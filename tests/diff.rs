@@ -0,0 +1,131 @@
+use arete_codegen::{EngineCatalogue, ModuleManifest};
+
+fn manifest(source: &str) -> ModuleManifest {
+    let module = arete_codegen::parse_source(source, "game.h", EngineCatalogue::default()).unwrap();
+    module.validate().unwrap();
+    module.manifest()
+}
+
+// (breaking, description) for each change
+fn diff(old: &ModuleManifest, new: &ModuleManifest) -> Vec<(bool, String)> {
+    arete_codegen::diff_manifests(old, new)
+        .into_iter()
+        .map(|change| (change.breaking, change.description))
+        .collect()
+}
+
+const BASE: &str = "
+struct Velocity { float x, y, z; };
+COMPONENT(Velocity);
+
+struct Score { int value; };
+RESOURCE(Score);
+
+SYSTEM(void integrate, Query<Transform&, const Velocity&> movers);
+";
+
+#[test]
+fn unchanged() {
+    assert!(diff(&manifest(BASE), &manifest(BASE)).is_empty());
+}
+
+#[test]
+fn added() {
+    let new = manifest(&format!(
+        "{BASE}
+struct Health {{ float value; }};
+COMPONENT(Health);
+
+SYSTEM(void tally, Score& score);
+"
+    ));
+
+    assert_eq!(
+        diff(&manifest(BASE), &new),
+        [
+            (false, String::from("component Health was added")),
+            (false, String::from("system tally was added")),
+        ]
+    );
+}
+
+#[test]
+fn removed() {
+    let new = manifest(
+        "
+struct Velocity { float x, y, z; };
+COMPONENT(Velocity);
+",
+    );
+
+    assert_eq!(
+        diff(&manifest(BASE), &new),
+        [
+            (true, String::from("resource Score was removed")),
+            (true, String::from("system integrate was removed")),
+        ]
+    );
+}
+
+#[test]
+fn changed_layout() {
+    let new = manifest(&BASE.replace("float x, y, z;", "double x, y, z;"));
+
+    assert_eq!(
+        diff(&manifest(BASE), &new),
+        [(true, String::from("component Velocity changed its fields"))]
+    );
+}
+
+#[test]
+fn changed_string_id() {
+    let old = manifest(BASE);
+
+    // renaming the struct changes its string id, but it keeps its layout
+    let renamed = manifest(&BASE.replace("Velocity", "Speed"));
+    assert_eq!(
+        diff(&old, &renamed),
+        [
+            (
+                true,
+                String::from(
+                    "component Velocity changed string id from game_module::Velocity to game_module::Speed"
+                )
+            ),
+            (true, String::from("system integrate changed its parameters")),
+        ]
+    );
+
+    // a manifest can give the same ident a different string id
+    let mut moved = manifest(BASE);
+    moved.components[1].string_id = String::from("game_module::HighScore");
+    assert_eq!(
+        diff(&old, &moved),
+        [(
+            true,
+            String::from(
+                "resource Score changed string id from game_module::Score to game_module::HighScore"
+            )
+        )]
+    );
+}
+
+#[test]
+fn reordered_system() {
+    let old = manifest(&format!("{BASE}SYSTEM(void tally, Score& score);\n"));
+    let new = manifest(&BASE.replace(
+        "SYSTEM(void integrate",
+        "SYSTEM(void tally, Score& score);\nSYSTEM(void integrate",
+    ));
+
+    assert_eq!(
+        diff(&old, &new),
+        [
+            (
+                true,
+                String::from("system integrate moved from index 0 to 1")
+            ),
+            (true, String::from("system tally moved from index 1 to 0")),
+        ]
+    );
+}
@@ -0,0 +1,10 @@
+#pragma once
+
+#include "arete.h"
+
+struct Gravity {
+    float strength;
+};
+RESOURCE(Gravity);
+
+SYSTEM(void fall, Gravity gravity);
@@ -0,0 +1,40 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z = 0.0f; // metres per second
+    Velocity() = default;
+    float length_squared() const { return x * x + y * y + z * z; }
+};
+COMPONENT(Velocity);
+
+/* followers keep a short trail of
+   the entities they chased */
+struct Follow final {
+    EntityId target;
+    EntityId trail[4];
+    static int count;
+    const char* label = "leader, then pack";
+    int flags{3};
+};
+COMPONENT(Follow);
+
+struct Tint {
+    Color color;
+    float amount;
+    uint8_t channels[3];
+    char grade;
+};
+COMPONENT(Tint);
+
+// the union and the alignas member aren't parsed, so it can't be mirrored
+struct Packet {
+    uint32_t sequence;
+    union {
+        float value;
+        int32_t count;
+    };
+    alignas(16) float weight;
+};
+COMPONENT(Packet);
@@ -0,0 +1,10 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z;
+};
+COMPONENT(Velocity);
+
+SYSTEM(void accelerate, Velocity& velocity);
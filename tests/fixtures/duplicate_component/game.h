@@ -0,0 +1,9 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z;
+};
+COMPONENT(Velocity);
+COMPONENT(Velocity);
@@ -0,0 +1,713 @@
+use std::ffi::{c_char, c_void, CStr};
+
+pub type ComponentId = u32;
+
+pub type EntityId = u64;
+
+pub type QueryForEachCallback = unsafe extern "C" fn(*mut c_void, *mut *mut c_void);
+
+pub type QueryParForEachCallback = unsafe extern "C" fn(*const c_void, *mut *mut c_void);
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentType(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl ComponentType {
+    pub const Component: Self = Self(0);
+    pub const Resource: Self = Self(1);
+}
+
+impl std::fmt::Debug for ComponentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Component => f.write_str("Component"),
+            Self::Resource => f.write_str("Resource"),
+            Self(value) => write!(f, "ComponentType({value})"),
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArgType(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl ArgType {
+    pub const DataAccessMut: Self = Self(0);
+    pub const DataAccessRef: Self = Self(1);
+    pub const Query: Self = Self(2);
+}
+
+impl std::fmt::Debug for ArgType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::DataAccessMut => f.write_str("DataAccessMut"),
+            Self::DataAccessRef => f.write_str("DataAccessRef"),
+            Self::Query => f.write_str("Query"),
+            Self(value) => write!(f, "ArgType({value})"),
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModuleStatus(pub i32);
+
+#[allow(non_upper_case_globals)]
+impl ModuleStatus {
+    pub const Ok: Self = Self(0);
+    pub const UnknownComponent: Self = Self(1);
+    pub const UnknownSystem: Self = Self(2);
+    pub const UnknownArg: Self = Self(3);
+    pub const UnknownQueryTerm: Self = Self(4);
+    pub const WrongArgType: Self = Self(5);
+    pub const MissingCallback: Self = Self(6);
+    pub const UnsupportedCallbacks: Self = Self(7);
+}
+
+impl std::fmt::Debug for ModuleStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Ok => f.write_str("Ok"),
+            Self::UnknownComponent => f.write_str("UnknownComponent"),
+            Self::UnknownSystem => f.write_str("UnknownSystem"),
+            Self::UnknownArg => f.write_str("UnknownArg"),
+            Self::UnknownQueryTerm => f.write_str("UnknownQueryTerm"),
+            Self::WrongArgType => f.write_str("WrongArgType"),
+            Self::MissingCallback => f.write_str("MissingCallback"),
+            Self::UnsupportedCallbacks => f.write_str("UnsupportedCallbacks"),
+            Self(value) => write!(f, "ModuleStatus({value})"),
+        }
+    }
+}
+
+pub type SystemFnPtr = unsafe extern "C" fn(*mut *mut c_void) -> i32;
+
+pub type SystemErrorNameFn = unsafe extern "C" fn(i32) -> *const c_char;
+
+pub type EntityMapFn = unsafe extern "C" fn(EntityId) -> EntityId;
+
+pub type QueryGetFn = unsafe extern "C" fn(*const c_void, EntityId, ComponentId) -> *const c_void;
+
+pub type QueryGetMutFn = unsafe extern "C" fn(*mut c_void, EntityId, ComponentId) -> *mut c_void;
+
+pub type QueryGetFirstFn = unsafe extern "C" fn(*const c_void, ComponentId) -> *const c_void;
+
+pub type QueryGetFirstMutFn = unsafe extern "C" fn(*mut c_void, ComponentId) -> *mut c_void;
+
+pub type QueryForEachFn = unsafe extern "C" fn(*mut c_void, QueryForEachCallback, *mut c_void);
+
+pub type QueryParForEachFn = unsafe extern "C" fn(*mut c_void, QueryParForEachCallback, *const c_void);
+
+pub type ReportErrorFn = unsafe extern "C" fn(*const c_char, *const c_char);
+
+pub type ProfileBeginFn = unsafe extern "C" fn(usize);
+
+pub type ProfileEndFn = unsafe extern "C" fn(usize);
+
+pub type QueryLenFn = unsafe extern "C" fn(*const c_void) -> usize;
+
+pub type QueryIsEmptyFn = unsafe extern "C" fn(*const c_void) -> bool;
+
+pub type QuerySingleFn = unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> usize;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EntityField {
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SourceLocation {
+    pub file: *const c_char,
+    pub line: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ComponentDescriptor {
+    pub string_id: *const c_char,
+    pub size: usize,
+    pub align: usize,
+    pub component_type: ComponentType,
+    pub entity_fields_len: usize,
+    pub entity_fields: *const EntityField,
+    pub name: *const c_char,
+    pub source: SourceLocation,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct QueryTermDescriptor {
+    pub arg_type: ArgType,
+    pub component: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ArgDescriptor {
+    pub arg_type: ArgType,
+    pub component: *const c_char,
+    pub component_type: ComponentType,
+    pub query_terms_len: usize,
+    pub query_terms: *const QueryTermDescriptor,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemDescriptor {
+    pub r#fn: Option<SystemFnPtr>,
+    pub is_once: bool,
+    pub args_len: usize,
+    pub args: *const ArgDescriptor,
+    pub error_name: Option<SystemErrorNameFn>,
+    pub name: *const c_char,
+    pub source: SourceLocation,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleDescriptor {
+    pub engine_version: u32,
+    pub components_len: usize,
+    pub components: *const ComponentDescriptor,
+    pub systems_len: usize,
+    pub systems: *const SystemDescriptor,
+}
+
+pub const ENGINE_CALLBACKS_VERSION: u32 = 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EngineCallbacks {
+    pub size: u32,
+    pub version: u32,
+    pub query_get: Option<QueryGetFn>,
+    pub query_get_mut: Option<QueryGetMutFn>,
+    pub query_get_first: Option<QueryGetFirstFn>,
+    pub query_get_first_mut: Option<QueryGetFirstMutFn>,
+    pub query_for_each: Option<QueryForEachFn>,
+    pub query_par_for_each: Option<QueryParForEachFn>,
+    pub report_error: Option<ReportErrorFn>,
+    pub profile_begin: Option<ProfileBeginFn>,
+    pub profile_end: Option<ProfileEndFn>,
+    pub query_len: Option<QueryLenFn>,
+    pub query_is_empty: Option<QueryIsEmptyFn>,
+    pub query_single: Option<QuerySingleFn>,
+}
+
+impl EngineCallbacks {
+    pub fn new() -> Self {
+        Self {
+            size: std::mem::size_of::<Self>() as u32,
+            version: ENGINE_CALLBACKS_VERSION,
+            query_get: None,
+            query_get_mut: None,
+            query_get_first: None,
+            query_get_first_mut: None,
+            query_for_each: None,
+            query_par_for_each: None,
+            report_error: None,
+            profile_begin: None,
+            profile_end: None,
+            query_len: None,
+            query_is_empty: None,
+            query_single: None,
+        }
+    }
+}
+
+impl Default for EngineCallbacks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MissingSymbol(pub &'static CStr);
+
+impl std::fmt::Display for MissingSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "game module is missing the symbol {:?}", self.0)
+    }
+}
+
+impl std::error::Error for MissingSymbol {}
+
+unsafe fn symbol<T: Copy>(
+    lookup: &mut impl FnMut(&CStr) -> *const c_void,
+    name: &'static CStr,
+) -> Result<T, MissingSymbol> {
+    let ptr = lookup(name);
+    if ptr.is_null() {
+        return Err(MissingSymbol(name));
+    }
+
+    Ok(std::mem::transmute_copy(&ptr))
+}
+
+/// Every function exported by a game module, resolved up front.
+pub struct ModuleApi {
+    arete_target_version: unsafe extern "C" fn() -> u32,
+    module_last_error: unsafe extern "C" fn() -> *const c_char,
+    module_init: unsafe extern "C" fn(*const EngineCallbacks) -> ModuleStatus,
+    component_string_id: unsafe extern "C" fn(usize) -> *const c_char,
+    try_component_size: unsafe extern "C" fn(*const c_char, *mut usize) -> ModuleStatus,
+    component_size: unsafe extern "C" fn(*const c_char) -> usize,
+    try_component_align: unsafe extern "C" fn(*const c_char, *mut usize) -> ModuleStatus,
+    component_align: unsafe extern "C" fn(*const c_char) -> usize,
+    try_component_type: unsafe extern "C" fn(*const c_char, *mut ComponentType) -> ModuleStatus,
+    component_type: unsafe extern "C" fn(*const c_char) -> ComponentType,
+    try_component_name: unsafe extern "C" fn(*const c_char, *mut *const c_char) -> ModuleStatus,
+    component_name: unsafe extern "C" fn(*const c_char) -> *const c_char,
+    try_component_source_location: unsafe extern "C" fn(*const c_char, *mut SourceLocation) -> ModuleStatus,
+    component_source_location: unsafe extern "C" fn(*const c_char) -> SourceLocation,
+    try_set_component_id: unsafe extern "C" fn(*const c_char, ComponentId) -> ModuleStatus,
+    set_component_id: unsafe extern "C" fn(*const c_char, ComponentId),
+    try_component_entity_fields: unsafe extern "C" fn(*const c_char, *mut usize, *mut *const EntityField) -> ModuleStatus,
+    component_entity_fields: unsafe extern "C" fn(*const c_char, *mut usize) -> *const EntityField,
+    try_component_remap_entities: unsafe extern "C" fn(*const c_char, *mut c_void, EntityMapFn) -> ModuleStatus,
+    component_remap_entities: unsafe extern "C" fn(*const c_char, *mut c_void, EntityMapFn),
+    try_resource_init: unsafe extern "C" fn(*const c_char, *mut c_void, *mut i32) -> ModuleStatus,
+    resource_init: unsafe extern "C" fn(*const c_char, *mut c_void) -> i32,
+    systems_len: unsafe extern "C" fn() -> usize,
+    try_system_name: unsafe extern "C" fn(usize, *mut *const c_char) -> ModuleStatus,
+    system_name: unsafe extern "C" fn(usize) -> *const c_char,
+    try_system_source_location: unsafe extern "C" fn(usize, *mut SourceLocation) -> ModuleStatus,
+    system_source_location: unsafe extern "C" fn(usize) -> SourceLocation,
+    try_system_is_once: unsafe extern "C" fn(usize, *mut bool) -> ModuleStatus,
+    system_is_once: unsafe extern "C" fn(usize) -> bool,
+    try_system_fn: unsafe extern "C" fn(usize, *mut SystemFnPtr) -> ModuleStatus,
+    system_fn: unsafe extern "C" fn(usize) -> SystemFnPtr,
+    try_system_error_name: unsafe extern "C" fn(usize, i32, *mut *const c_char) -> ModuleStatus,
+    system_error_name: unsafe extern "C" fn(usize, i32) -> *const c_char,
+    try_system_args_len: unsafe extern "C" fn(usize, *mut usize) -> ModuleStatus,
+    system_args_len: unsafe extern "C" fn(usize) -> usize,
+    try_system_arg_type: unsafe extern "C" fn(usize, usize, *mut ArgType) -> ModuleStatus,
+    system_arg_type: unsafe extern "C" fn(usize, usize) -> ArgType,
+    try_system_arg_component: unsafe extern "C" fn(usize, usize, *mut *const c_char) -> ModuleStatus,
+    system_arg_component: unsafe extern "C" fn(usize, usize) -> *const c_char,
+    try_system_query_args_len: unsafe extern "C" fn(usize, usize, *mut usize) -> ModuleStatus,
+    system_query_args_len: unsafe extern "C" fn(usize, usize) -> usize,
+    try_system_query_arg_type: unsafe extern "C" fn(usize, usize, usize, *mut ArgType) -> ModuleStatus,
+    system_query_arg_type: unsafe extern "C" fn(usize, usize, usize) -> ArgType,
+    try_system_query_arg_component: unsafe extern "C" fn(usize, usize, usize, *mut *const c_char) -> ModuleStatus,
+    system_query_arg_component: unsafe extern "C" fn(usize, usize, usize) -> *const c_char,
+    arete_module_descriptor: unsafe extern "C" fn() -> *const ModuleDescriptor,
+    arete_abi_hash: unsafe extern "C" fn() -> u64,
+}
+
+impl ModuleApi {
+    /// # Safety
+    ///
+    /// `lookup` must resolve symbols of a game module generated by arete-codegen, and the
+    /// module must stay loaded for as long as the returned value is used.
+    pub unsafe fn load(
+        mut lookup: impl FnMut(&CStr) -> *const c_void,
+    ) -> Result<Self, MissingSymbol> {
+        Ok(Self {
+            arete_target_version: symbol(&mut lookup, c"arete_target_version")?,
+            module_last_error: symbol(&mut lookup, c"module_last_error")?,
+            module_init: symbol(&mut lookup, c"module_init")?,
+            component_string_id: symbol(&mut lookup, c"component_string_id")?,
+            try_component_size: symbol(&mut lookup, c"try_component_size")?,
+            component_size: symbol(&mut lookup, c"component_size")?,
+            try_component_align: symbol(&mut lookup, c"try_component_align")?,
+            component_align: symbol(&mut lookup, c"component_align")?,
+            try_component_type: symbol(&mut lookup, c"try_component_type")?,
+            component_type: symbol(&mut lookup, c"component_type")?,
+            try_component_name: symbol(&mut lookup, c"try_component_name")?,
+            component_name: symbol(&mut lookup, c"component_name")?,
+            try_component_source_location: symbol(&mut lookup, c"try_component_source_location")?,
+            component_source_location: symbol(&mut lookup, c"component_source_location")?,
+            try_set_component_id: symbol(&mut lookup, c"try_set_component_id")?,
+            set_component_id: symbol(&mut lookup, c"set_component_id")?,
+            try_component_entity_fields: symbol(&mut lookup, c"try_component_entity_fields")?,
+            component_entity_fields: symbol(&mut lookup, c"component_entity_fields")?,
+            try_component_remap_entities: symbol(&mut lookup, c"try_component_remap_entities")?,
+            component_remap_entities: symbol(&mut lookup, c"component_remap_entities")?,
+            try_resource_init: symbol(&mut lookup, c"try_resource_init")?,
+            resource_init: symbol(&mut lookup, c"resource_init")?,
+            systems_len: symbol(&mut lookup, c"systems_len")?,
+            try_system_name: symbol(&mut lookup, c"try_system_name")?,
+            system_name: symbol(&mut lookup, c"system_name")?,
+            try_system_source_location: symbol(&mut lookup, c"try_system_source_location")?,
+            system_source_location: symbol(&mut lookup, c"system_source_location")?,
+            try_system_is_once: symbol(&mut lookup, c"try_system_is_once")?,
+            system_is_once: symbol(&mut lookup, c"system_is_once")?,
+            try_system_fn: symbol(&mut lookup, c"try_system_fn")?,
+            system_fn: symbol(&mut lookup, c"system_fn")?,
+            try_system_error_name: symbol(&mut lookup, c"try_system_error_name")?,
+            system_error_name: symbol(&mut lookup, c"system_error_name")?,
+            try_system_args_len: symbol(&mut lookup, c"try_system_args_len")?,
+            system_args_len: symbol(&mut lookup, c"system_args_len")?,
+            try_system_arg_type: symbol(&mut lookup, c"try_system_arg_type")?,
+            system_arg_type: symbol(&mut lookup, c"system_arg_type")?,
+            try_system_arg_component: symbol(&mut lookup, c"try_system_arg_component")?,
+            system_arg_component: symbol(&mut lookup, c"system_arg_component")?,
+            try_system_query_args_len: symbol(&mut lookup, c"try_system_query_args_len")?,
+            system_query_args_len: symbol(&mut lookup, c"system_query_args_len")?,
+            try_system_query_arg_type: symbol(&mut lookup, c"try_system_query_arg_type")?,
+            system_query_arg_type: symbol(&mut lookup, c"system_query_arg_type")?,
+            try_system_query_arg_component: symbol(&mut lookup, c"try_system_query_arg_component")?,
+            system_query_arg_component: symbol(&mut lookup, c"system_query_arg_component")?,
+            arete_module_descriptor: symbol(&mut lookup, c"arete_module_descriptor")?,
+            arete_abi_hash: symbol(&mut lookup, c"arete_abi_hash")?,
+        })
+    }
+
+    pub fn arete_target_version(&self) -> u32 {
+        unsafe { (self.arete_target_version)() }
+    }
+
+    pub fn module_last_error(&self) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.module_last_error)();
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn module_init(&self, callbacks: *const EngineCallbacks) -> ModuleStatus {
+        (self.module_init)(callbacks)
+    }
+
+    pub fn component_string_id(&self, index: usize) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.component_string_id)(index);
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_size(&self, string_id: &CStr, out: *mut usize) -> ModuleStatus {
+        (self.try_component_size)(string_id.as_ptr(), out)
+    }
+
+    pub fn component_size(&self, string_id: &CStr) -> usize {
+        unsafe { (self.component_size)(string_id.as_ptr()) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_align(&self, string_id: &CStr, out: *mut usize) -> ModuleStatus {
+        (self.try_component_align)(string_id.as_ptr(), out)
+    }
+
+    pub fn component_align(&self, string_id: &CStr) -> usize {
+        unsafe { (self.component_align)(string_id.as_ptr()) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_type(&self, string_id: &CStr, out: *mut ComponentType) -> ModuleStatus {
+        (self.try_component_type)(string_id.as_ptr(), out)
+    }
+
+    pub fn component_type(&self, string_id: &CStr) -> ComponentType {
+        unsafe { (self.component_type)(string_id.as_ptr()) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_name(&self, string_id: &CStr, out: *mut *const c_char) -> ModuleStatus {
+        (self.try_component_name)(string_id.as_ptr(), out)
+    }
+
+    pub fn component_name(&self, string_id: &CStr) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.component_name)(string_id.as_ptr());
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_source_location(&self, string_id: &CStr, out: *mut SourceLocation) -> ModuleStatus {
+        (self.try_component_source_location)(string_id.as_ptr(), out)
+    }
+
+    pub fn component_source_location(&self, string_id: &CStr) -> SourceLocation {
+        unsafe { (self.component_source_location)(string_id.as_ptr()) }
+    }
+
+    pub fn try_set_component_id(&self, string_id: &CStr, id: ComponentId) -> ModuleStatus {
+        unsafe { (self.try_set_component_id)(string_id.as_ptr(), id) }
+    }
+
+    pub fn set_component_id(&self, string_id: &CStr, id: ComponentId) {
+        unsafe { (self.set_component_id)(string_id.as_ptr(), id) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_entity_fields(&self, string_id: &CStr, len: *mut usize, out: *mut *const EntityField) -> ModuleStatus {
+        (self.try_component_entity_fields)(string_id.as_ptr(), len, out)
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn component_entity_fields(&self, string_id: &CStr, len: *mut usize) -> *const EntityField {
+        (self.component_entity_fields)(string_id.as_ptr(), len)
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_component_remap_entities(&self, string_id: &CStr, val: *mut c_void, map_fn: EntityMapFn) -> ModuleStatus {
+        (self.try_component_remap_entities)(string_id.as_ptr(), val, map_fn)
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn component_remap_entities(&self, string_id: &CStr, val: *mut c_void, map_fn: EntityMapFn) {
+        (self.component_remap_entities)(string_id.as_ptr(), val, map_fn)
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_resource_init(&self, string_id: &CStr, val: *mut c_void, out: *mut i32) -> ModuleStatus {
+        (self.try_resource_init)(string_id.as_ptr(), val, out)
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn resource_init(&self, string_id: &CStr, val: *mut c_void) -> i32 {
+        (self.resource_init)(string_id.as_ptr(), val)
+    }
+
+    pub fn systems_len(&self) -> usize {
+        unsafe { (self.systems_len)() }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_name(&self, system_index: usize, out: *mut *const c_char) -> ModuleStatus {
+        (self.try_system_name)(system_index, out)
+    }
+
+    pub fn system_name(&self, system_index: usize) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.system_name)(system_index);
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_source_location(&self, system_index: usize, out: *mut SourceLocation) -> ModuleStatus {
+        (self.try_system_source_location)(system_index, out)
+    }
+
+    pub fn system_source_location(&self, system_index: usize) -> SourceLocation {
+        unsafe { (self.system_source_location)(system_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_is_once(&self, system_index: usize, out: *mut bool) -> ModuleStatus {
+        (self.try_system_is_once)(system_index, out)
+    }
+
+    pub fn system_is_once(&self, system_index: usize) -> bool {
+        unsafe { (self.system_is_once)(system_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_fn(&self, system_index: usize, out: *mut SystemFnPtr) -> ModuleStatus {
+        (self.try_system_fn)(system_index, out)
+    }
+
+    pub fn system_fn(&self, system_index: usize) -> SystemFnPtr {
+        unsafe { (self.system_fn)(system_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_error_name(&self, system_index: usize, code: i32, out: *mut *const c_char) -> ModuleStatus {
+        (self.try_system_error_name)(system_index, code, out)
+    }
+
+    pub fn system_error_name(&self, system_index: usize, code: i32) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.system_error_name)(system_index, code);
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_args_len(&self, system_index: usize, out: *mut usize) -> ModuleStatus {
+        (self.try_system_args_len)(system_index, out)
+    }
+
+    pub fn system_args_len(&self, system_index: usize) -> usize {
+        unsafe { (self.system_args_len)(system_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_arg_type(&self, system_index: usize, arg_index: usize, out: *mut ArgType) -> ModuleStatus {
+        (self.try_system_arg_type)(system_index, arg_index, out)
+    }
+
+    pub fn system_arg_type(&self, system_index: usize, arg_index: usize) -> ArgType {
+        unsafe { (self.system_arg_type)(system_index, arg_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_arg_component(&self, system_index: usize, arg_index: usize, out: *mut *const c_char) -> ModuleStatus {
+        (self.try_system_arg_component)(system_index, arg_index, out)
+    }
+
+    pub fn system_arg_component(&self, system_index: usize, arg_index: usize) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.system_arg_component)(system_index, arg_index);
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_query_args_len(&self, system_index: usize, arg_index: usize, out: *mut usize) -> ModuleStatus {
+        (self.try_system_query_args_len)(system_index, arg_index, out)
+    }
+
+    pub fn system_query_args_len(&self, system_index: usize, arg_index: usize) -> usize {
+        unsafe { (self.system_query_args_len)(system_index, arg_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_query_arg_type(&self, system_index: usize, arg_index: usize, query_index: usize, out: *mut ArgType) -> ModuleStatus {
+        (self.try_system_query_arg_type)(system_index, arg_index, query_index, out)
+    }
+
+    pub fn system_query_arg_type(&self, system_index: usize, arg_index: usize, query_index: usize) -> ArgType {
+        unsafe { (self.system_query_arg_type)(system_index, arg_index, query_index) }
+    }
+
+    /// # Safety
+    ///
+    /// Pointer arguments must be valid for the generated implementation to access.
+    pub unsafe fn try_system_query_arg_component(&self, system_index: usize, arg_index: usize, query_index: usize, out: *mut *const c_char) -> ModuleStatus {
+        (self.try_system_query_arg_component)(system_index, arg_index, query_index, out)
+    }
+
+    pub fn system_query_arg_component(&self, system_index: usize, arg_index: usize, query_index: usize) -> Option<&CStr> {
+        unsafe {
+            let ptr = (self.system_query_arg_component)(system_index, arg_index, query_index);
+            (!ptr.is_null()).then(|| CStr::from_ptr(ptr))
+        }
+    }
+
+    pub fn arete_module_descriptor(&self) -> *const ModuleDescriptor {
+        unsafe { (self.arete_module_descriptor)() }
+    }
+
+    pub fn arete_abi_hash(&self) -> u64 {
+        unsafe { (self.arete_abi_hash)() }
+    }
+}
+
+/// What `arete_abi_hash` returns for the module these bindings were generated from.
+pub const ARETE_ABI_HASH: u64 = 0xf5b9a485f62bf2ca;
+
+const ABI_CALLBACKS: &str = "callback QueryGetFn const void*(const void*, EntityId, ComponentId)\ncallback QueryGetMutFn void*(void*, EntityId, ComponentId)\ncallback QueryGetFirstFn const void*(const void*, ComponentId)\ncallback QueryGetFirstMutFn void*(void*, ComponentId)\ncallback QueryForEachFn void(void*, QueryForEachCallback, void*)\ncallback QueryParForEachFn void(void*, QueryParForEachCallback, const void*)\ncallback ReportErrorFn void(const char*, const char*)\ncallback ProfileBeginFn void(size_t)\ncallback ProfileEndFn void(size_t)\ncallback QueryLenFn size_t(const void*)\ncallback QueryIsEmptyFn bool(const void*)\ncallback QuerySingleFn size_t(void*, void**)\n";
+
+/// What `arete_abi_hash` should return for a module with this descriptor, given the string id,
+/// size and alignment of each type in the engine catalogue.
+///
+/// # Safety
+///
+/// `module` must be the descriptor returned by the module's `arete_module_descriptor`.
+pub unsafe fn expected_abi_hash(module: *const ModuleDescriptor, public: &[(&str, usize, usize)]) -> u64 {
+    let string = |ptr: *const c_char| CStr::from_ptr(ptr).to_string_lossy();
+    let access = |arg_type: ArgType| match arg_type {
+        ArgType::DataAccessMut => "mut",
+        ArgType::DataAccessRef => "ref",
+        ArgType::Query => "query",
+        _ => "unknown",
+    };
+
+    let module = &*module;
+    let mut description = String::new();
+    let mut inputs = std::collections::BTreeSet::new();
+
+    for i in 0..module.components_len {
+        let component = &*module.components.add(i);
+        let kind = match component.component_type {
+            ComponentType::Component => "component",
+            ComponentType::Resource => "resource",
+            _ => "unknown",
+        };
+        description += &format!("component {} {kind}\n", string(component.string_id));
+    }
+
+    for i in 0..module.systems_len {
+        let system = &*module.systems.add(i);
+        description += if system.is_once { "system once\n" } else { "system always\n" };
+
+        for j in 0..system.args_len {
+            let arg = &*system.args.add(j);
+            if arg.arg_type != ArgType::Query {
+                description += &format!("arg {} {}\n", access(arg.arg_type), string(arg.component));
+                inputs.insert(string(arg.component));
+                continue;
+            }
+
+            description += "arg query\n";
+            for k in 0..arg.query_terms_len {
+                let term = &*arg.query_terms.add(k);
+                description += &format!("term {} {}\n", access(term.arg_type), string(term.component));
+                inputs.insert(string(term.component));
+            }
+        }
+    }
+
+    description += ABI_CALLBACKS;
+
+    // 64-bit FNV-1a, continued over the layouts of the engine types the systems take, in string
+    // id order, as little-endian `uint64_t`s
+    let layouts = inputs
+        .iter()
+        .filter_map(|input| public.iter().find(|(string_id, ..)| string_id == input))
+        .flat_map(|&(_, size, align)| [size as u64, align as u64])
+        .flat_map(u64::to_le_bytes);
+
+    let mut hash = 0xcbf29ce484222325u64;
+    for b in description.bytes().chain(layouts) {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
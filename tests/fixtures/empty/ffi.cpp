@@ -0,0 +1,570 @@
+#include "game.h"
+#include <cstdarg>
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <exception>
+#include <memory>
+#include <type_traits>
+
+static_assert(ENGINE_VERSION == 1, "engine manifest is for a different engine version");
+
+extern "C" uint32_t arete_target_version() {
+    return ENGINE_VERSION;
+}
+
+typedef enum ComponentType {
+    ComponentTypeComponent,
+    ComponentTypeResource,
+} ComponentType;
+
+typedef enum ArgType {
+    ArgTypeDataAccessMut,
+    ArgTypeDataAccessRef,
+    ArgTypeQuery,
+} ArgType;
+
+typedef enum ModuleStatus {
+    ModuleStatusOk,
+    ModuleStatusUnknownComponent,
+    ModuleStatusUnknownSystem,
+    ModuleStatusUnknownArg,
+    ModuleStatusUnknownQueryTerm,
+    ModuleStatusWrongArgType,
+    ModuleStatusMissingCallback,
+    ModuleStatusUnsupportedCallbacks,
+} ModuleStatus;
+
+typedef int32_t (*system_fn_ptr)(void**);
+
+typedef const char* (*system_error_name_fn)(int32_t);
+
+typedef EntityId (*entity_map_fn)(EntityId);
+
+typedef const void* (*query_get_fn)(const void*, EntityId, ComponentId);
+
+typedef void* (*query_get_mut_fn)(void*, EntityId, ComponentId);
+
+typedef const void* (*query_get_first_fn)(const void*, ComponentId);
+
+typedef void* (*query_get_first_mut_fn)(void*, ComponentId);
+
+typedef void (*query_for_each_fn)(void*, QueryForEachCallback, void*);
+
+typedef void (*query_par_for_each_fn)(void*, QueryParForEachCallback, const void*);
+
+typedef void (*report_error_fn)(const char*, const char*);
+
+typedef void (*profile_begin_fn)(size_t);
+
+typedef void (*profile_end_fn)(size_t);
+
+typedef size_t (*query_len_fn)(const void*);
+
+typedef bool (*query_is_empty_fn)(const void*);
+
+typedef size_t (*query_single_fn)(void*, void**);
+
+typedef struct EntityField {
+    size_t offset;
+    size_t len;
+} EntityField;
+
+typedef struct SourceLocation {
+    const char* file;
+    uint32_t line;
+} SourceLocation;
+
+typedef struct ComponentDescriptor {
+    const char* string_id;
+    size_t size;
+    size_t align;
+    ComponentType component_type;
+    size_t entity_fields_len;
+    const EntityField* entity_fields;
+    const char* name;
+    SourceLocation source;
+} ComponentDescriptor;
+
+typedef struct QueryTermDescriptor {
+    ArgType arg_type;
+    const char* component;
+} QueryTermDescriptor;
+
+typedef struct ArgDescriptor {
+    ArgType arg_type;
+    const char* component;
+    ComponentType component_type;
+    size_t query_terms_len;
+    const QueryTermDescriptor* query_terms;
+} ArgDescriptor;
+
+typedef struct SystemDescriptor {
+    system_fn_ptr fn;
+    bool is_once;
+    size_t args_len;
+    const ArgDescriptor* args;
+    system_error_name_fn error_name;
+    const char* name;
+    SourceLocation source;
+} SystemDescriptor;
+
+typedef struct ModuleDescriptor {
+    uint32_t engine_version;
+    size_t components_len;
+    const ComponentDescriptor* components;
+    size_t systems_len;
+    const SystemDescriptor* systems;
+} ModuleDescriptor;
+
+#define ENGINE_CALLBACKS_VERSION 1
+
+typedef struct EngineCallbacks {
+    uint32_t size;
+    uint32_t version;
+    query_get_fn query_get;
+    query_get_mut_fn query_get_mut;
+    query_get_first_fn query_get_first;
+    query_get_first_mut_fn query_get_first_mut;
+    query_for_each_fn query_for_each;
+    query_par_for_each_fn query_par_for_each;
+    report_error_fn report_error;
+    profile_begin_fn profile_begin;
+    profile_end_fn profile_end;
+    query_len_fn query_len;
+    query_is_empty_fn query_is_empty;
+    query_single_fn query_single;
+} EngineCallbacks;
+
+static thread_local char LAST_ERROR[256];
+
+static ModuleStatus fail(ModuleStatus status, const char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    std::vsnprintf(LAST_ERROR, sizeof(LAST_ERROR), format, args);
+    va_end(args);
+
+    return status;
+}
+
+extern "C" const char* module_last_error() {
+    return LAST_ERROR[0] != '\0' ? LAST_ERROR : nullptr;
+}
+
+const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+const void* (*QueryGetFirstFn)(const void*, ComponentId);
+void* (*QueryGetFirstMutFn)(void*, ComponentId);
+void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+void (*ReportErrorFn)(const char*, const char*);
+void (*ProfileBeginFn)(size_t);
+void (*ProfileEndFn)(size_t);
+size_t (*QueryLenFn)(const void*);
+bool (*QueryIsEmptyFn)(const void*);
+size_t (*QuerySingleFn)(void*, void**);
+
+extern "C" ModuleStatus module_init(const EngineCallbacks* callbacks) {
+    if (callbacks == nullptr ||
+        callbacks->size < offsetof(EngineCallbacks, query_get) ||
+        callbacks->version != ENGINE_CALLBACKS_VERSION) {
+        return fail(
+            ModuleStatusUnsupportedCallbacks,
+            "unsupported engine callbacks, expected version %d",
+            ENGINE_CALLBACKS_VERSION
+        );
+    }
+
+    EngineCallbacks provided = {};
+    std::memcpy(
+        &provided,
+        callbacks,
+        callbacks->size < sizeof(EngineCallbacks) ? callbacks->size : sizeof(EngineCallbacks)
+    );
+
+    QueryGetFn = provided.query_get;
+    QueryGetMutFn = provided.query_get_mut;
+    QueryGetFirstFn = provided.query_get_first;
+    QueryGetFirstMutFn = provided.query_get_first_mut;
+    QueryForEachFn = provided.query_for_each;
+    QueryParForEachFn = provided.query_par_for_each;
+    ReportErrorFn = provided.report_error;
+    ProfileBeginFn = provided.profile_begin;
+    ProfileEndFn = provided.profile_end;
+    QueryLenFn = provided.query_len;
+    QueryIsEmptyFn = provided.query_is_empty;
+    QuerySingleFn = provided.query_single;
+
+    return ModuleStatusOk;
+}
+
+static int32_t component_index(const char* string_id) {
+    return -1;
+}
+
+extern "C" const char* component_string_id(size_t index) {
+    return nullptr;
+}
+
+extern "C" ModuleStatus try_component_size(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" size_t component_size(const char* string_id) {
+    size_t value {};
+    if (try_component_size(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_size: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_align(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" size_t component_align(const char* string_id) {
+    size_t value {};
+    if (try_component_align(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_align: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_type(const char* string_id, ComponentType* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" ComponentType component_type(const char* string_id) {
+    ComponentType value {};
+    if (try_component_type(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_name(const char* string_id, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" const char* component_name(const char* string_id) {
+    const char* value {};
+    if (try_component_name(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_source_location(const char* string_id, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" SourceLocation component_source_location(const char* string_id) {
+    SourceLocation value {};
+    if (try_component_source_location(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+struct ComponentIdSlot {
+    const char* string_id;
+    ComponentId* id;
+};
+
+static const ComponentIdSlot COMPONENT_ID_SLOTS[] = {
+};
+
+static int32_t component_id_index(const char* string_id) {
+    return -1;
+}
+
+extern "C" ModuleStatus try_set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *COMPONENT_ID_SLOTS[index].id = id;
+    return ModuleStatusOk;
+}
+
+extern "C" void set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index >= 0) {
+        *COMPONENT_ID_SLOTS[index].id = id;
+    }
+}
+
+extern "C" ModuleStatus try_component_entity_fields(const char* string_id, size_t* len, const EntityField** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" const EntityField* component_entity_fields(const char* string_id, size_t* len) {
+    const EntityField* value {};
+    if (try_component_entity_fields(string_id, len, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_entity_fields: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+}
+
+extern "C" void component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    if (try_component_remap_entities(string_id, val, map_fn) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_remap_entities: %s\n", LAST_ERROR);
+        std::abort();
+    }
+}
+
+extern "C" ModuleStatus try_resource_init(const char* string_id, void* val, int32_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    switch (component_index(string_id)) {
+        default:
+            return fail(ModuleStatusUnknownComponent, "unknown resource %s", string_id);
+    }
+}
+
+extern "C" int32_t resource_init(const char* string_id, void* val) {
+    int32_t value {};
+    if (try_resource_init(string_id, val, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "resource_init: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" size_t systems_len() {
+    return 0;
+}
+
+extern "C" ModuleStatus try_system_name(size_t system_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" const char* system_name(size_t system_index) {
+    const char* value {};
+    if (try_system_name(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_source_location(size_t system_index, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" SourceLocation system_source_location(size_t system_index) {
+    SourceLocation value {};
+    if (try_system_source_location(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_is_once(size_t system_index, bool* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" bool system_is_once(size_t system_index) {
+    bool value {};
+    if (try_system_is_once(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_is_once: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_fn(size_t system_index, system_fn_ptr* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" system_fn_ptr system_fn(size_t system_index) {
+    system_fn_ptr value {};
+    if (try_system_fn(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_fn: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_error_name(size_t system_index, int32_t code, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" const char* system_error_name(size_t system_index, int32_t code) {
+    const char* value {};
+    if (try_system_error_name(system_index, code, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_error_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_args_len(size_t system_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" size_t system_args_len(size_t system_index) {
+    size_t value {};
+    if (try_system_args_len(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_type(size_t system_index, size_t arg_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" ArgType system_arg_type(size_t system_index, size_t arg_index) {
+    ArgType value {};
+    if (try_system_arg_type(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_component(size_t system_index, size_t arg_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" const char* system_arg_component(size_t system_index, size_t arg_index) {
+    const char* value {};
+    if (try_system_arg_component(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_args_len(size_t system_index, size_t arg_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" size_t system_query_args_len(size_t system_index, size_t arg_index) {
+    size_t value {};
+    if (try_system_query_args_len(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" ArgType system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index) {
+    ArgType value {};
+    if (try_system_query_arg_type(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    return fail(ModuleStatusUnknownSystem, "unknown system %zu, the module has none", system_index);
+}
+
+extern "C" const char* system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index) {
+    const char* value {};
+    if (try_system_query_arg_component(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+static const ModuleDescriptor MODULE_DESCRIPTOR = {
+    ENGINE_VERSION,
+    0,
+    nullptr,
+    0,
+    nullptr,
+};
+
+extern "C" const ModuleDescriptor* arete_module_descriptor() {
+    return &MODULE_DESCRIPTOR;
+}
+
+static constexpr uint64_t compute_abi_hash() {
+    uint64_t hash = 0xf5b9a485f62bf2caull;
+
+    return hash;
+}
+
+extern "C" uint64_t arete_abi_hash() {
+    constexpr uint64_t hash = compute_abi_hash();
+    return hash;
+}
+
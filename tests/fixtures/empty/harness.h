@@ -0,0 +1,287 @@
+#pragma once
+
+#include "game.h"
+#include "ffi.h"
+#include <algorithm>
+#include <cstddef>
+#include <cstdint>
+#include <cstdlib>
+#include <memory>
+#include <string>
+#include <utility>
+#include <vector>
+
+namespace arete_test {
+
+inline void register_component_ids() {
+}
+
+class World;
+
+struct QueryHandle {
+    World* world;
+    std::vector<ComponentId> terms;
+};
+
+class World {
+public:
+    World() {
+        register_component_ids();
+
+        EngineCallbacks callbacks = engine_callbacks();
+        if (module_init(&callbacks) != ModuleStatusOk) {
+            std::abort();
+        }
+
+        last_error_.clear();
+    }
+
+    // the callbacks answered by the fake world, for tests to extend and pass to module_init again
+    static EngineCallbacks engine_callbacks() {
+        EngineCallbacks callbacks = {};
+        callbacks.size = sizeof(EngineCallbacks);
+        callbacks.version = ENGINE_CALLBACKS_VERSION;
+        callbacks.query_get = &query_get;
+        callbacks.query_get_mut = &query_get_mut;
+        callbacks.query_get_first = &query_get_first;
+        callbacks.query_get_first_mut = &query_get_first_mut;
+        callbacks.query_for_each = &query_for_each;
+        callbacks.query_par_for_each = &query_par_for_each;
+        callbacks.report_error = &report_error;
+        callbacks.query_len = &query_len;
+        callbacks.query_is_empty = &query_is_empty;
+        callbacks.query_single = &query_single;
+        return callbacks;
+    }
+
+    template <typename... Ts>
+    EntityId spawn(const Ts&... components) {
+        EntityId entity = ++last_entity_;
+        entities_.push_back({ entity, {} });
+        (insert(entity, components), ...);
+        return entity;
+    }
+
+    // replaces the component if the entity already has one
+    template <typename T>
+    T& insert(EntityId entity, const T& component) {
+        Entity* found = find_entity(entity);
+        if (found == nullptr) {
+            std::abort();
+        }
+
+        std::shared_ptr<void> value = std::make_shared<T>(component);
+        for (auto& [id, existing] : found->components) {
+            if (id == Component<T>::ID) {
+                existing = value;
+                return *static_cast<T*>(value.get());
+            }
+        }
+
+        found->components.push_back({ Component<T>::ID, value });
+        return *static_cast<T*>(value.get());
+    }
+
+    template <typename T>
+    T* get(EntityId entity) {
+        return static_cast<T*>(find_component(entity, Component<T>::ID));
+    }
+
+    // value-initialized on first use
+    template <typename T>
+    T& resource() {
+        for (auto& [id, value] : resources_) {
+            if (id == Component<T>::ID) {
+                return *static_cast<T*>(value.get());
+            }
+        }
+
+        std::shared_ptr<void> value = std::make_shared<T>();
+        resources_.push_back({ Component<T>::ID, value });
+        return *static_cast<T*>(value.get());
+    }
+
+    template <typename T>
+    void insert_resource(const T& value) {
+        resource<T>() = value;
+    }
+
+    size_t entities_len() const {
+        return entities_.size();
+    }
+
+    // "<system>: <what()>" for the last exception that escaped a system, empty if none did
+    const std::string& last_error() const {
+        return last_error_;
+    }
+
+private:
+    struct Entity {
+        EntityId id;
+        std::vector<std::pair<ComponentId, std::shared_ptr<void>>> components;
+    };
+
+    Entity* find_entity(EntityId entity) {
+        for (Entity& candidate : entities_) {
+            if (candidate.id == entity) {
+                return &candidate;
+            }
+        }
+
+        return nullptr;
+    }
+
+    static void* component_of(Entity& entity, ComponentId id) {
+        for (auto& [component_id, value] : entity.components) {
+            if (component_id == id) {
+                return value.get();
+            }
+        }
+
+        return nullptr;
+    }
+
+    void* find_component(EntityId entity, ComponentId id) {
+        Entity* found = find_entity(entity);
+        return found != nullptr ? component_of(*found, id) : nullptr;
+    }
+
+    // fills `components` with the entity's value for each term, if it has all of them
+    static bool match(Entity& entity, const QueryHandle& query, std::vector<void*>& components) {
+        components.clear();
+        for (ComponentId term : query.terms) {
+            void* component = component_of(entity, term);
+            if (component == nullptr) {
+                return false;
+            }
+
+            components.push_back(component);
+        }
+
+        return true;
+    }
+
+    static bool has_term(const QueryHandle& query, ComponentId id) {
+        for (ComponentId term : query.terms) {
+            if (term == id) {
+                return true;
+            }
+        }
+
+        return false;
+    }
+
+    static void* query_component(const QueryHandle& query, EntityId entity, ComponentId id) {
+        Entity* found = query.world->find_entity(entity);
+        std::vector<void*> components;
+        if (found == nullptr || !has_term(query, id) || !match(*found, query, components)) {
+            return nullptr;
+        }
+
+        return component_of(*found, id);
+    }
+
+    static void* query_first(const QueryHandle& query, ComponentId id) {
+        if (!has_term(query, id)) {
+            return nullptr;
+        }
+
+        std::vector<void*> components;
+        for (Entity& entity : query.world->entities_) {
+            if (match(entity, query, components)) {
+                return component_of(entity, id);
+            }
+        }
+
+        return nullptr;
+    }
+
+    static const void* query_get(const void* handle, EntityId entity, ComponentId id) {
+        return query_component(*static_cast<const QueryHandle*>(handle), entity, id);
+    }
+
+    static void* query_get_mut(void* handle, EntityId entity, ComponentId id) {
+        return query_component(*static_cast<const QueryHandle*>(handle), entity, id);
+    }
+
+    static const void* query_get_first(const void* handle, ComponentId id) {
+        return query_first(*static_cast<const QueryHandle*>(handle), id);
+    }
+
+    static void* query_get_first_mut(void* handle, ComponentId id) {
+        return query_first(*static_cast<const QueryHandle*>(handle), id);
+    }
+
+    static void query_for_each(void* handle, QueryForEachCallback callback, void* user_data) {
+        const QueryHandle& query = *static_cast<const QueryHandle*>(handle);
+
+        std::vector<void*> components;
+        for (Entity& entity : query.world->entities_) {
+            if (match(entity, query, components)) {
+                callback(user_data, components.data());
+            }
+        }
+    }
+
+    static void query_par_for_each(void* handle, QueryParForEachCallback callback, const void* user_data) {
+        const QueryHandle& query = *static_cast<const QueryHandle*>(handle);
+
+        std::vector<void*> components;
+        for (Entity& entity : query.world->entities_) {
+            if (match(entity, query, components)) {
+                callback(user_data, components.data());
+            }
+        }
+    }
+
+    static size_t query_len(const void* handle) {
+        const QueryHandle& query = *static_cast<const QueryHandle*>(handle);
+
+        size_t len = 0;
+        std::vector<void*> components;
+        for (Entity& entity : query.world->entities_) {
+            if (match(entity, query, components)) {
+                len += 1;
+            }
+        }
+
+        return len;
+    }
+
+    static bool query_is_empty(const void* handle) {
+        return query_len(handle) == 0;
+    }
+
+    static size_t query_single(void* handle, void** out) {
+        const QueryHandle& query = *static_cast<const QueryHandle*>(handle);
+
+        size_t len = 0;
+        std::vector<void*> components;
+        std::vector<void*> single;
+        for (Entity& entity : query.world->entities_) {
+            if (match(entity, query, components)) {
+                len += 1;
+                single = components;
+            }
+        }
+
+        if (len == 1) {
+            std::copy(single.begin(), single.end(), out);
+        }
+
+        return len;
+    }
+
+    static void report_error(const char* context, const char* message) {
+        last_error_ = std::string(context) + ": " + message;
+    }
+
+    // the callbacks get no user data to find the world through
+    static inline std::string last_error_;
+
+    EntityId last_entity_ = 0;
+    std::vector<Entity> entities_;
+    std::vector<std::pair<ComponentId, std::shared_ptr<void>>> resources_;
+};
+
+} // namespace arete_test
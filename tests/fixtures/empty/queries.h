@@ -0,0 +1,30 @@
+#pragma once
+
+#include "game.h"
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <optional>
+#include <tuple>
+
+// defined in ffi.cpp, and set by the engine through module_init
+extern const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+extern void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+extern const void* (*QueryGetFirstFn)(const void*, ComponentId);
+extern void* (*QueryGetFirstMutFn)(void*, ComponentId);
+extern void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+extern void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+extern void (*ReportErrorFn)(const char*, const char*);
+extern void (*ProfileBeginFn)(size_t);
+extern void (*ProfileEndFn)(size_t);
+extern size_t (*QueryLenFn)(const void*);
+extern bool (*QueryIsEmptyFn)(const void*);
+extern size_t (*QuerySingleFn)(void*, void**);
+
+template <typename... Ts>
+struct TypedQuery;
+
+// `TypedQuery movers(query);` picks the specialization for the system's argument
+template <typename... Ts>
+TypedQuery(Query<Ts...>) -> TypedQuery<Ts...>;
+
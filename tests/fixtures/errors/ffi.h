@@ -0,0 +1,244 @@
+#ifndef ARETE_MODULE_FFI_H
+#define ARETE_MODULE_FFI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifndef ARETE_ENGINE_TYPES
+#define ARETE_ENGINE_TYPES
+typedef uint32_t ComponentId;
+typedef uint64_t EntityId;
+typedef void (*QueryForEachCallback)(void*, void**);
+typedef void (*QueryParForEachCallback)(const void*, void**);
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum ComponentType {
+    ComponentTypeComponent,
+    ComponentTypeResource,
+} ComponentType;
+
+typedef enum ArgType {
+    ArgTypeDataAccessMut,
+    ArgTypeDataAccessRef,
+    ArgTypeQuery,
+} ArgType;
+
+typedef enum ModuleStatus {
+    ModuleStatusOk,
+    ModuleStatusUnknownComponent,
+    ModuleStatusUnknownSystem,
+    ModuleStatusUnknownArg,
+    ModuleStatusUnknownQueryTerm,
+    ModuleStatusWrongArgType,
+    ModuleStatusMissingCallback,
+    ModuleStatusUnsupportedCallbacks,
+} ModuleStatus;
+
+typedef int32_t (*system_fn_ptr)(void**);
+
+typedef const char* (*system_error_name_fn)(int32_t);
+
+typedef EntityId (*entity_map_fn)(EntityId);
+
+typedef const void* (*query_get_fn)(const void*, EntityId, ComponentId);
+
+typedef void* (*query_get_mut_fn)(void*, EntityId, ComponentId);
+
+typedef const void* (*query_get_first_fn)(const void*, ComponentId);
+
+typedef void* (*query_get_first_mut_fn)(void*, ComponentId);
+
+typedef void (*query_for_each_fn)(void*, QueryForEachCallback, void*);
+
+typedef void (*query_par_for_each_fn)(void*, QueryParForEachCallback, const void*);
+
+typedef void (*report_error_fn)(const char*, const char*);
+
+typedef void (*profile_begin_fn)(size_t);
+
+typedef void (*profile_end_fn)(size_t);
+
+typedef size_t (*query_len_fn)(const void*);
+
+typedef bool (*query_is_empty_fn)(const void*);
+
+typedef size_t (*query_single_fn)(void*, void**);
+
+typedef struct EntityField {
+    size_t offset;
+    size_t len;
+} EntityField;
+
+typedef struct SourceLocation {
+    const char* file;
+    uint32_t line;
+} SourceLocation;
+
+typedef struct ComponentDescriptor {
+    const char* string_id;
+    size_t size;
+    size_t align;
+    ComponentType component_type;
+    size_t entity_fields_len;
+    const EntityField* entity_fields;
+    const char* name;
+    SourceLocation source;
+} ComponentDescriptor;
+
+typedef struct QueryTermDescriptor {
+    ArgType arg_type;
+    const char* component;
+} QueryTermDescriptor;
+
+typedef struct ArgDescriptor {
+    ArgType arg_type;
+    const char* component;
+    ComponentType component_type;
+    size_t query_terms_len;
+    const QueryTermDescriptor* query_terms;
+} ArgDescriptor;
+
+typedef struct SystemDescriptor {
+    system_fn_ptr fn;
+    bool is_once;
+    size_t args_len;
+    const ArgDescriptor* args;
+    system_error_name_fn error_name;
+    const char* name;
+    SourceLocation source;
+} SystemDescriptor;
+
+typedef struct ModuleDescriptor {
+    uint32_t engine_version;
+    size_t components_len;
+    const ComponentDescriptor* components;
+    size_t systems_len;
+    const SystemDescriptor* systems;
+} ModuleDescriptor;
+
+#define ENGINE_CALLBACKS_VERSION 1
+
+typedef struct EngineCallbacks {
+    uint32_t size;
+    uint32_t version;
+    query_get_fn query_get;
+    query_get_mut_fn query_get_mut;
+    query_get_first_fn query_get_first;
+    query_get_first_mut_fn query_get_first_mut;
+    query_for_each_fn query_for_each;
+    query_par_for_each_fn query_par_for_each;
+    report_error_fn report_error;
+    profile_begin_fn profile_begin;
+    profile_end_fn profile_end;
+    query_len_fn query_len;
+    query_is_empty_fn query_is_empty;
+    query_single_fn query_single;
+} EngineCallbacks;
+
+// what `arete_abi_hash` returns when the module matches this header
+#define ARETE_ABI_HASH 0x634e9cba6bbcf01cull
+
+uint32_t arete_target_version(void);
+
+const char* module_last_error(void);
+
+ModuleStatus module_init(const EngineCallbacks* callbacks);
+
+const char* component_string_id(size_t index);
+
+ModuleStatus try_component_size(const char* string_id, size_t* out);
+
+size_t component_size(const char* string_id);
+
+ModuleStatus try_component_align(const char* string_id, size_t* out);
+
+size_t component_align(const char* string_id);
+
+ModuleStatus try_component_type(const char* string_id, ComponentType* out);
+
+ComponentType component_type(const char* string_id);
+
+ModuleStatus try_component_name(const char* string_id, const char** out);
+
+const char* component_name(const char* string_id);
+
+ModuleStatus try_component_source_location(const char* string_id, SourceLocation* out);
+
+SourceLocation component_source_location(const char* string_id);
+
+ModuleStatus try_set_component_id(const char* string_id, ComponentId id);
+
+void set_component_id(const char* string_id, ComponentId id);
+
+ModuleStatus try_component_entity_fields(const char* string_id, size_t* len, const EntityField** out);
+
+const EntityField* component_entity_fields(const char* string_id, size_t* len);
+
+ModuleStatus try_component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn);
+
+void component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn);
+
+ModuleStatus try_resource_init(const char* string_id, void* val, int32_t* out);
+
+int32_t resource_init(const char* string_id, void* val);
+
+size_t systems_len(void);
+
+ModuleStatus try_system_name(size_t system_index, const char** out);
+
+const char* system_name(size_t system_index);
+
+ModuleStatus try_system_source_location(size_t system_index, SourceLocation* out);
+
+SourceLocation system_source_location(size_t system_index);
+
+ModuleStatus try_system_is_once(size_t system_index, bool* out);
+
+bool system_is_once(size_t system_index);
+
+ModuleStatus try_system_fn(size_t system_index, system_fn_ptr* out);
+
+system_fn_ptr system_fn(size_t system_index);
+
+ModuleStatus try_system_error_name(size_t system_index, int32_t code, const char** out);
+
+const char* system_error_name(size_t system_index, int32_t code);
+
+ModuleStatus try_system_args_len(size_t system_index, size_t* out);
+
+size_t system_args_len(size_t system_index);
+
+ModuleStatus try_system_arg_type(size_t system_index, size_t arg_index, ArgType* out);
+
+ArgType system_arg_type(size_t system_index, size_t arg_index);
+
+ModuleStatus try_system_arg_component(size_t system_index, size_t arg_index, const char** out);
+
+const char* system_arg_component(size_t system_index, size_t arg_index);
+
+ModuleStatus try_system_query_args_len(size_t system_index, size_t arg_index, size_t* out);
+
+size_t system_query_args_len(size_t system_index, size_t arg_index);
+
+ModuleStatus try_system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index, ArgType* out);
+
+ArgType system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index);
+
+ModuleStatus try_system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index, const char** out);
+
+const char* system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index);
+
+const ModuleDescriptor* arete_module_descriptor(void);
+
+uint64_t arete_abi_hash(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
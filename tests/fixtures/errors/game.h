@@ -0,0 +1,20 @@
+#pragma once
+
+#include "arete.h"
+
+struct Health {
+    int32_t current, max;
+};
+COMPONENT(Health);
+
+enum class SpawnError : int32_t {
+    Ok,
+    NoSpace,
+    Full = 1,
+    TooMany = 4,
+};
+
+SYSTEM(bool regenerate, Query<Health&> healths, const Time& time);
+SYSTEM(SpawnError spawn_waves, const Time& time);
+SYSTEM(AreteResult check_health, Query<const Health&> healths);
+SYSTEM_ONCE(void setup, const Window& window);
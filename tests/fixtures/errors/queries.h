@@ -0,0 +1,232 @@
+#pragma once
+
+#include "game.h"
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <optional>
+#include <tuple>
+
+// defined in ffi.cpp, and set by the engine through module_init
+extern const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+extern void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+extern const void* (*QueryGetFirstFn)(const void*, ComponentId);
+extern void* (*QueryGetFirstMutFn)(void*, ComponentId);
+extern void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+extern void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+extern void (*ReportErrorFn)(const char*, const char*);
+extern void (*ProfileBeginFn)(size_t);
+extern void (*ProfileEndFn)(size_t);
+extern size_t (*QueryLenFn)(const void*);
+extern bool (*QueryIsEmptyFn)(const void*);
+extern size_t (*QuerySingleFn)(void*, void**);
+
+template <typename... Ts>
+struct TypedQuery;
+
+// `TypedQuery movers(query);` picks the specialization for the system's argument
+template <typename... Ts>
+TypedQuery(Query<Ts...>) -> TypedQuery<Ts...>;
+
+template <>
+struct TypedQuery<Health&> {
+    using Item = std::tuple<Health&>;
+
+    void* handle;
+
+    explicit TypedQuery(Query<Health&> query) : handle(query.handle) {}
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            (*static_cast<F*>(user_data))(
+                *static_cast<Health*>(components[0])
+            );
+        };
+
+        QueryForEachFn(handle, callback, &f);
+    }
+
+    template <typename F>
+    void par_for_each(const F& f) {
+        QueryParForEachCallback callback = [](const void* user_data, void** components) {
+            (*static_cast<const F*>(user_data))(
+                *static_cast<Health*>(components[0])
+            );
+        };
+
+        QueryParForEachFn(handle, callback, &f);
+    }
+
+    std::optional<Item> get(EntityId entity) {
+        Health* term_0 = static_cast<Health*>(QueryGetMutFn(handle, entity, Component<Health>::ID));
+
+        if (term_0 == nullptr) {
+            return std::nullopt;
+        }
+
+        return Item(*term_0);
+    }
+
+    size_t len() const {
+        if (QueryLenFn == nullptr) {
+            return count(nullptr);
+        }
+
+        return QueryLenFn(handle);
+    }
+
+    bool is_empty() const {
+        if (QueryIsEmptyFn == nullptr) {
+            return count(nullptr) == 0;
+        }
+
+        return QueryIsEmptyFn(handle);
+    }
+
+    std::optional<Item> single() {
+        void* components[1] = {};
+        size_t matches = QuerySingleFn != nullptr
+            ? QuerySingleFn(handle, components)
+            : count(components);
+
+        if (matches != 1) {
+            if (ReportErrorFn != nullptr) {
+                char message[64];
+                std::snprintf(message, sizeof(message), "expected one match, found %zu", matches);
+                ReportErrorFn("TypedQuery::single", message);
+            }
+
+            return std::nullopt;
+        }
+
+        return Item(
+            *static_cast<Health*>(components[0])
+        );
+    }
+
+private:
+    size_t count(void** first) const {
+        struct Counter {
+            size_t matches;
+            void** first;
+        };
+
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            Counter* counter = static_cast<Counter*>(user_data);
+            if (counter->matches == 0 && counter->first != nullptr) {
+                for (size_t i = 0; i < 1; ++i) {
+                    counter->first[i] = components[i];
+                }
+            }
+
+            ++counter->matches;
+        };
+
+        Counter counter = { 0, first };
+        QueryForEachFn(handle, callback, &counter);
+        return counter.matches;
+    }
+};
+
+template <>
+struct TypedQuery<const Health&> {
+    using Item = std::tuple<const Health&>;
+
+    void* handle;
+
+    explicit TypedQuery(Query<const Health&> query) : handle(query.handle) {}
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            (*static_cast<F*>(user_data))(
+                *static_cast<const Health*>(components[0])
+            );
+        };
+
+        QueryForEachFn(handle, callback, &f);
+    }
+
+    template <typename F>
+    void par_for_each(const F& f) {
+        QueryParForEachCallback callback = [](const void* user_data, void** components) {
+            (*static_cast<const F*>(user_data))(
+                *static_cast<const Health*>(components[0])
+            );
+        };
+
+        QueryParForEachFn(handle, callback, &f);
+    }
+
+    std::optional<Item> get(EntityId entity) {
+        const Health* term_0 = static_cast<const Health*>(QueryGetFn(handle, entity, Component<Health>::ID));
+
+        if (term_0 == nullptr) {
+            return std::nullopt;
+        }
+
+        return Item(*term_0);
+    }
+
+    size_t len() const {
+        if (QueryLenFn == nullptr) {
+            return count(nullptr);
+        }
+
+        return QueryLenFn(handle);
+    }
+
+    bool is_empty() const {
+        if (QueryIsEmptyFn == nullptr) {
+            return count(nullptr) == 0;
+        }
+
+        return QueryIsEmptyFn(handle);
+    }
+
+    std::optional<Item> single() {
+        void* components[1] = {};
+        size_t matches = QuerySingleFn != nullptr
+            ? QuerySingleFn(handle, components)
+            : count(components);
+
+        if (matches != 1) {
+            if (ReportErrorFn != nullptr) {
+                char message[64];
+                std::snprintf(message, sizeof(message), "expected one match, found %zu", matches);
+                ReportErrorFn("TypedQuery::single", message);
+            }
+
+            return std::nullopt;
+        }
+
+        return Item(
+            *static_cast<const Health*>(components[0])
+        );
+    }
+
+private:
+    size_t count(void** first) const {
+        struct Counter {
+            size_t matches;
+            void** first;
+        };
+
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            Counter* counter = static_cast<Counter*>(user_data);
+            if (counter->matches == 0 && counter->first != nullptr) {
+                for (size_t i = 0; i < 1; ++i) {
+                    counter->first[i] = components[i];
+                }
+            }
+
+            ++counter->matches;
+        };
+
+        Counter counter = { 0, first };
+        QueryForEachFn(handle, callback, &counter);
+        return counter.matches;
+    }
+};
+
@@ -0,0 +1,904 @@
+#include "game.h"
+#include <cstdarg>
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <exception>
+#include <memory>
+#include <type_traits>
+
+static_assert(ENGINE_VERSION == 1, "engine manifest is for a different engine version");
+static_assert(sizeof(Input) == 256, "size of Input does not match the engine manifest");
+static_assert(alignof(Input) == 1, "alignment of Input does not match the engine manifest");
+static_assert(sizeof(Window) == 8, "size of Window does not match the engine manifest");
+static_assert(alignof(Window) == 4, "alignment of Window does not match the engine manifest");
+
+extern "C" uint32_t arete_target_version() {
+    return ENGINE_VERSION;
+}
+
+static uint32_t string_id_hash(const char* string_id, uint32_t seed) {
+    uint32_t hash = 2166136261u ^ seed;
+    for (; *string_id != '\0'; ++string_id) {
+        hash ^= static_cast<uint8_t>(*string_id);
+        hash *= 16777619u;
+    }
+
+    hash ^= hash >> 16;
+    hash *= 0x85ebca6bu;
+    hash ^= hash >> 13;
+    hash *= 0xc2b2ae35u;
+    hash ^= hash >> 16;
+
+    return hash;
+}
+
+typedef enum ComponentType {
+    ComponentTypeComponent,
+    ComponentTypeResource,
+} ComponentType;
+
+typedef enum ArgType {
+    ArgTypeDataAccessMut,
+    ArgTypeDataAccessRef,
+    ArgTypeQuery,
+} ArgType;
+
+typedef enum ModuleStatus {
+    ModuleStatusOk,
+    ModuleStatusUnknownComponent,
+    ModuleStatusUnknownSystem,
+    ModuleStatusUnknownArg,
+    ModuleStatusUnknownQueryTerm,
+    ModuleStatusWrongArgType,
+    ModuleStatusMissingCallback,
+    ModuleStatusUnsupportedCallbacks,
+} ModuleStatus;
+
+typedef int32_t (*system_fn_ptr)(void**);
+
+typedef const char* (*system_error_name_fn)(int32_t);
+
+typedef EntityId (*entity_map_fn)(EntityId);
+
+typedef const void* (*query_get_fn)(const void*, EntityId, ComponentId);
+
+typedef void* (*query_get_mut_fn)(void*, EntityId, ComponentId);
+
+typedef const void* (*query_get_first_fn)(const void*, ComponentId);
+
+typedef void* (*query_get_first_mut_fn)(void*, ComponentId);
+
+typedef void (*query_for_each_fn)(void*, QueryForEachCallback, void*);
+
+typedef void (*query_par_for_each_fn)(void*, QueryParForEachCallback, const void*);
+
+typedef void (*report_error_fn)(const char*, const char*);
+
+typedef void (*profile_begin_fn)(size_t);
+
+typedef void (*profile_end_fn)(size_t);
+
+typedef size_t (*query_len_fn)(const void*);
+
+typedef bool (*query_is_empty_fn)(const void*);
+
+typedef size_t (*query_single_fn)(void*, void**);
+
+typedef struct EntityField {
+    size_t offset;
+    size_t len;
+} EntityField;
+
+typedef struct SourceLocation {
+    const char* file;
+    uint32_t line;
+} SourceLocation;
+
+typedef struct ComponentDescriptor {
+    const char* string_id;
+    size_t size;
+    size_t align;
+    ComponentType component_type;
+    size_t entity_fields_len;
+    const EntityField* entity_fields;
+    const char* name;
+    SourceLocation source;
+} ComponentDescriptor;
+
+typedef struct QueryTermDescriptor {
+    ArgType arg_type;
+    const char* component;
+} QueryTermDescriptor;
+
+typedef struct ArgDescriptor {
+    ArgType arg_type;
+    const char* component;
+    ComponentType component_type;
+    size_t query_terms_len;
+    const QueryTermDescriptor* query_terms;
+} ArgDescriptor;
+
+typedef struct SystemDescriptor {
+    system_fn_ptr fn;
+    bool is_once;
+    size_t args_len;
+    const ArgDescriptor* args;
+    system_error_name_fn error_name;
+    const char* name;
+    SourceLocation source;
+} SystemDescriptor;
+
+typedef struct ModuleDescriptor {
+    uint32_t engine_version;
+    size_t components_len;
+    const ComponentDescriptor* components;
+    size_t systems_len;
+    const SystemDescriptor* systems;
+} ModuleDescriptor;
+
+#define ENGINE_CALLBACKS_VERSION 1
+
+typedef struct EngineCallbacks {
+    uint32_t size;
+    uint32_t version;
+    query_get_fn query_get;
+    query_get_mut_fn query_get_mut;
+    query_get_first_fn query_get_first;
+    query_get_first_mut_fn query_get_first_mut;
+    query_for_each_fn query_for_each;
+    query_par_for_each_fn query_par_for_each;
+    report_error_fn report_error;
+    profile_begin_fn profile_begin;
+    profile_end_fn profile_end;
+    query_len_fn query_len;
+    query_is_empty_fn query_is_empty;
+    query_single_fn query_single;
+} EngineCallbacks;
+
+static thread_local char LAST_ERROR[256];
+
+static ModuleStatus fail(ModuleStatus status, const char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    std::vsnprintf(LAST_ERROR, sizeof(LAST_ERROR), format, args);
+    va_end(args);
+
+    return status;
+}
+
+extern "C" const char* module_last_error() {
+    return LAST_ERROR[0] != '\0' ? LAST_ERROR : nullptr;
+}
+
+const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+const void* (*QueryGetFirstFn)(const void*, ComponentId);
+void* (*QueryGetFirstMutFn)(void*, ComponentId);
+void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+void (*ReportErrorFn)(const char*, const char*);
+void (*ProfileBeginFn)(size_t);
+void (*ProfileEndFn)(size_t);
+size_t (*QueryLenFn)(const void*);
+bool (*QueryIsEmptyFn)(const void*);
+size_t (*QuerySingleFn)(void*, void**);
+
+static void report_error(const char* context, const char* message) {
+    if (ReportErrorFn != nullptr) {
+        ReportErrorFn(context, message);
+    }
+}
+
+extern "C" ModuleStatus module_init(const EngineCallbacks* callbacks) {
+    if (callbacks == nullptr ||
+        callbacks->size < offsetof(EngineCallbacks, query_get) ||
+        callbacks->version != ENGINE_CALLBACKS_VERSION) {
+        return fail(
+            ModuleStatusUnsupportedCallbacks,
+            "unsupported engine callbacks, expected version %d",
+            ENGINE_CALLBACKS_VERSION
+        );
+    }
+
+    EngineCallbacks provided = {};
+    std::memcpy(
+        &provided,
+        callbacks,
+        callbacks->size < sizeof(EngineCallbacks) ? callbacks->size : sizeof(EngineCallbacks)
+    );
+
+    QueryGetFn = provided.query_get;
+    QueryGetMutFn = provided.query_get_mut;
+    QueryGetFirstFn = provided.query_get_first;
+    QueryGetFirstMutFn = provided.query_get_first_mut;
+    QueryForEachFn = provided.query_for_each;
+    QueryParForEachFn = provided.query_par_for_each;
+    ReportErrorFn = provided.report_error;
+    ProfileBeginFn = provided.profile_begin;
+    ProfileEndFn = provided.profile_end;
+    QueryLenFn = provided.query_len;
+    QueryIsEmptyFn = provided.query_is_empty;
+    QuerySingleFn = provided.query_single;
+
+    return ModuleStatusOk;
+}
+
+static const ComponentDescriptor COMPONENTS[] = {
+    {
+        "game_module::Spawner",
+        sizeof(Spawner),
+        alignof(Spawner),
+        ComponentTypeResource,
+        0,
+        nullptr,
+        "Spawner",
+        { "game.h", 5 },
+    },
+};
+
+static const int32_t COMPONENT_HASH_DISPLACEMENTS[] = {
+    -1,
+};
+
+static const int32_t COMPONENT_HASH_SLOTS[] = {
+    0,
+};
+
+static int32_t component_index(const char* string_id) {
+    int32_t displacement = COMPONENT_HASH_DISPLACEMENTS[string_id_hash(string_id, 0) % 1];
+    uint32_t slot = displacement < 0
+        ? static_cast<uint32_t>(-displacement - 1)
+        : string_id_hash(string_id, displacement) % 1;
+    int32_t index = COMPONENT_HASH_SLOTS[slot];
+
+    if (std::strcmp(string_id, COMPONENTS[index].string_id) != 0) {
+        return -1;
+    }
+
+    return index;
+}
+
+extern "C" const char* component_string_id(size_t index) {
+    if (index >= 1) {
+        return nullptr;
+    }
+
+    return COMPONENTS[index].string_id;
+}
+
+extern "C" ModuleStatus try_component_size(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].size;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t component_size(const char* string_id) {
+    size_t value {};
+    if (try_component_size(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_size: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_align(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].align;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t component_align(const char* string_id) {
+    size_t value {};
+    if (try_component_align(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_align: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_type(const char* string_id, ComponentType* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].component_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ComponentType component_type(const char* string_id) {
+    ComponentType value {};
+    if (try_component_type(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_name(const char* string_id, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].name;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* component_name(const char* string_id) {
+    const char* value {};
+    if (try_component_name(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_source_location(const char* string_id, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].source;
+    return ModuleStatusOk;
+}
+
+extern "C" SourceLocation component_source_location(const char* string_id) {
+    SourceLocation value {};
+    if (try_component_source_location(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+struct ComponentIdSlot {
+    const char* string_id;
+    ComponentId* id;
+};
+
+static const ComponentIdSlot COMPONENT_ID_SLOTS[] = {
+    { "arete_public::Input", &Component<Input>::ID },
+    { "game_module::Spawner", &Component<Spawner>::ID },
+    { "arete_public::Window", &Component<Window>::ID },
+};
+
+static const int32_t COMPONENT_ID_HASH_DISPLACEMENTS[] = {
+    -3, -2, -1,
+};
+
+static const int32_t COMPONENT_ID_HASH_SLOTS[] = {
+    2, 1, 0,
+};
+
+static int32_t component_id_index(const char* string_id) {
+    int32_t displacement = COMPONENT_ID_HASH_DISPLACEMENTS[string_id_hash(string_id, 0) % 3];
+    uint32_t slot = displacement < 0
+        ? static_cast<uint32_t>(-displacement - 1)
+        : string_id_hash(string_id, displacement) % 3;
+    int32_t index = COMPONENT_ID_HASH_SLOTS[slot];
+
+    if (std::strcmp(string_id, COMPONENT_ID_SLOTS[index].string_id) != 0) {
+        return -1;
+    }
+
+    return index;
+}
+
+extern "C" ModuleStatus try_set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *COMPONENT_ID_SLOTS[index].id = id;
+    return ModuleStatusOk;
+}
+
+extern "C" void set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index >= 0) {
+        *COMPONENT_ID_SLOTS[index].id = id;
+    }
+}
+
+extern "C" ModuleStatus try_component_entity_fields(const char* string_id, size_t* len, const EntityField** out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *len = COMPONENTS[index].entity_fields_len;
+    *out = COMPONENTS[index].entity_fields;
+    return ModuleStatusOk;
+}
+
+extern "C" const EntityField* component_entity_fields(const char* string_id, size_t* len) {
+    const EntityField* value {};
+    if (try_component_entity_fields(string_id, len, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_entity_fields: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    switch (index) {
+        default: break;
+    }
+
+    return ModuleStatusOk;
+}
+
+extern "C" void component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    if (try_component_remap_entities(string_id, val, map_fn) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_remap_entities: %s\n", LAST_ERROR);
+        std::abort();
+    }
+}
+
+template <typename T>
+static int32_t construct_resource(const char* string_id, void* val) {
+    try {
+        std::construct_at(static_cast<T*>(val));
+        return 0;
+    } catch (const std::exception& e) {
+        report_error(string_id, e.what());
+        return -1;
+    } catch (...) {
+        report_error(string_id, "unknown exception");
+        return -1;
+    }
+}
+
+extern "C" ModuleStatus try_resource_init(const char* string_id, void* val, int32_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    switch (component_index(string_id)) {
+        case 0:
+            *out = construct_resource<Spawner>(string_id, val);
+            return ModuleStatusOk;
+        default:
+            return fail(ModuleStatusUnknownComponent, "unknown resource %s", string_id);
+    }
+}
+
+extern "C" int32_t resource_init(const char* string_id, void* val) {
+    int32_t value {};
+    if (try_resource_init(string_id, val, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "resource_init: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+int32_t setup_ffi(void** input) {
+    try {
+        setup(
+            *static_cast<Spawner*>(input[0])
+        );
+
+        return 0;
+    } catch (const std::exception& e) {
+        report_error("setup", e.what());
+        return -1;
+    } catch (...) {
+        report_error("setup", "unknown exception");
+        return -1;
+    }
+}
+
+int32_t load_level_ffi(void** input) {
+    try {
+        load_level(
+            *static_cast<const Window*>(input[0])
+        );
+
+        return 0;
+    } catch (const std::exception& e) {
+        report_error("load_level", e.what());
+        return -1;
+    } catch (...) {
+        report_error("load_level", "unknown exception");
+        return -1;
+    }
+}
+
+int32_t spawn_ffi(void** input) {
+    try {
+        spawn(
+            *static_cast<Spawner*>(input[0]),
+            *static_cast<const Input*>(input[1])
+        );
+
+        return 0;
+    } catch (const std::exception& e) {
+        report_error("spawn", e.what());
+        return -1;
+    } catch (...) {
+        report_error("spawn", "unknown exception");
+        return -1;
+    }
+}
+
+static const ArgDescriptor setup_ARGS[] = {
+    { ArgTypeDataAccessMut, "game_module::Spawner", ComponentTypeResource, 0, nullptr },
+};
+
+static const ArgDescriptor load_level_ARGS[] = {
+    { ArgTypeDataAccessRef, "arete_public::Window", ComponentTypeResource, 0, nullptr },
+};
+
+static const ArgDescriptor spawn_ARGS[] = {
+    { ArgTypeDataAccessMut, "game_module::Spawner", ComponentTypeResource, 0, nullptr },
+    { ArgTypeDataAccessRef, "arete_public::Input", ComponentTypeResource, 0, nullptr },
+};
+
+static const SystemDescriptor SYSTEMS[] = {
+    { setup_ffi, true, 1, setup_ARGS, nullptr, "setup", { "game.h", 10 } },
+    { load_level_ffi, true, 1, load_level_ARGS, nullptr, "load_level", { "game.h", 11 } },
+    { spawn_ffi, false, 2, spawn_ARGS, nullptr, "spawn", { "game.h", 12 } },
+};
+
+extern "C" size_t systems_len() {
+    return 3;
+}
+
+extern "C" ModuleStatus try_system_name(size_t system_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].name;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_name(size_t system_index) {
+    const char* value {};
+    if (try_system_name(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_source_location(size_t system_index, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].source;
+    return ModuleStatusOk;
+}
+
+extern "C" SourceLocation system_source_location(size_t system_index) {
+    SourceLocation value {};
+    if (try_system_source_location(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_is_once(size_t system_index, bool* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].is_once;
+    return ModuleStatusOk;
+}
+
+extern "C" bool system_is_once(size_t system_index) {
+    bool value {};
+    if (try_system_is_once(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_is_once: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_fn(size_t system_index, system_fn_ptr* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].fn;
+    return ModuleStatusOk;
+}
+
+extern "C" system_fn_ptr system_fn(size_t system_index) {
+    system_fn_ptr value {};
+    if (try_system_fn(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_fn: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_error_name(size_t system_index, int32_t code, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    *out = system.error_name != nullptr ? system.error_name(code) : nullptr;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_error_name(size_t system_index, int32_t code) {
+    const char* value {};
+    if (try_system_error_name(system_index, code, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_error_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_args_len(size_t system_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].args_len;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t system_args_len(size_t system_index) {
+    size_t value {};
+    if (try_system_args_len(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_type(size_t system_index, size_t arg_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    *out = system.args[arg_index].arg_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ArgType system_arg_type(size_t system_index, size_t arg_index) {
+    ArgType value {};
+    if (try_system_arg_type(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_component(size_t system_index, size_t arg_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    if (system.args[arg_index].component == nullptr) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is a query", arg_index, system_index);
+    }
+
+    *out = system.args[arg_index].component;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_arg_component(size_t system_index, size_t arg_index) {
+    const char* value {};
+    if (try_system_arg_component(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_args_len(size_t system_index, size_t arg_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    if (system.args[arg_index].arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    *out = system.args[arg_index].query_terms_len;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t system_query_args_len(size_t system_index, size_t arg_index) {
+    size_t value {};
+    if (try_system_query_args_len(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    const ArgDescriptor& arg = system.args[arg_index];
+    if (arg.arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    if (query_index >= arg.query_terms_len) {
+        return fail(ModuleStatusUnknownQueryTerm, "argument %zu of system %zu has no term %zu", arg_index, system_index, query_index);
+    }
+
+    *out = arg.query_terms[query_index].arg_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ArgType system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index) {
+    ArgType value {};
+    if (try_system_query_arg_type(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 3) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    const ArgDescriptor& arg = system.args[arg_index];
+    if (arg.arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    if (query_index >= arg.query_terms_len) {
+        return fail(ModuleStatusUnknownQueryTerm, "argument %zu of system %zu has no term %zu", arg_index, system_index, query_index);
+    }
+
+    *out = arg.query_terms[query_index].component;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index) {
+    const char* value {};
+    if (try_system_query_arg_component(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+static const ModuleDescriptor MODULE_DESCRIPTOR = {
+    ENGINE_VERSION,
+    1,
+    COMPONENTS,
+    3,
+    SYSTEMS,
+};
+
+extern "C" const ModuleDescriptor* arete_module_descriptor() {
+    return &MODULE_DESCRIPTOR;
+}
+
+static constexpr uint64_t compute_abi_hash() {
+    uint64_t hash = 0x148bfca9a6ae21d6ull;
+
+    const uint64_t layouts[] = {
+        sizeof(Input),
+        alignof(Input),
+        sizeof(Window),
+        alignof(Window),
+    };
+
+    for (uint64_t value : layouts) {
+        for (int i = 0; i < 8; ++i) {
+            hash ^= (value >> (i * 8)) & 0xff;
+            hash *= 0x100000001b3ull;
+        }
+    }
+
+    return hash;
+}
+
+extern "C" uint64_t arete_abi_hash() {
+    constexpr uint64_t hash = compute_abi_hash();
+    return hash;
+}
+
@@ -0,0 +1,12 @@
+#pragma once
+
+#include "arete.h"
+
+struct Spawner {
+    uint32_t remaining;
+};
+RESOURCE(Spawner);
+
+SYSTEM_ONCE(void setup, Spawner& spawner);
+SYSTEM_ONCE(void load_level, const Window& window);
+SYSTEM(void spawn, Spawner& spawner, const Input& input);
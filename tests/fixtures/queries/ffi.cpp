@@ -0,0 +1,940 @@
+#include "game.h"
+#include <cstdarg>
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <exception>
+#include <memory>
+#include <type_traits>
+
+static_assert(ENGINE_VERSION == 1, "engine manifest is for a different engine version");
+static_assert(sizeof(Camera) == 12, "size of Camera does not match the engine manifest");
+static_assert(alignof(Camera) == 4, "alignment of Camera does not match the engine manifest");
+static_assert(sizeof(Color) == 16, "size of Color does not match the engine manifest");
+static_assert(alignof(Color) == 4, "alignment of Color does not match the engine manifest");
+static_assert(sizeof(Time) == 16, "size of Time does not match the engine manifest");
+static_assert(alignof(Time) == 8, "alignment of Time does not match the engine manifest");
+static_assert(sizeof(Transform) == 40, "size of Transform does not match the engine manifest");
+static_assert(alignof(Transform) == 4, "alignment of Transform does not match the engine manifest");
+static_assert(sizeof(Window) == 8, "size of Window does not match the engine manifest");
+static_assert(alignof(Window) == 4, "alignment of Window does not match the engine manifest");
+
+extern "C" uint32_t arete_target_version() {
+    return ENGINE_VERSION;
+}
+
+static uint32_t string_id_hash(const char* string_id, uint32_t seed) {
+    uint32_t hash = 2166136261u ^ seed;
+    for (; *string_id != '\0'; ++string_id) {
+        hash ^= static_cast<uint8_t>(*string_id);
+        hash *= 16777619u;
+    }
+
+    hash ^= hash >> 16;
+    hash *= 0x85ebca6bu;
+    hash ^= hash >> 13;
+    hash *= 0xc2b2ae35u;
+    hash ^= hash >> 16;
+
+    return hash;
+}
+
+typedef enum ComponentType {
+    ComponentTypeComponent,
+    ComponentTypeResource,
+} ComponentType;
+
+typedef enum ArgType {
+    ArgTypeDataAccessMut,
+    ArgTypeDataAccessRef,
+    ArgTypeQuery,
+} ArgType;
+
+typedef enum ModuleStatus {
+    ModuleStatusOk,
+    ModuleStatusUnknownComponent,
+    ModuleStatusUnknownSystem,
+    ModuleStatusUnknownArg,
+    ModuleStatusUnknownQueryTerm,
+    ModuleStatusWrongArgType,
+    ModuleStatusMissingCallback,
+    ModuleStatusUnsupportedCallbacks,
+} ModuleStatus;
+
+typedef int32_t (*system_fn_ptr)(void**);
+
+typedef const char* (*system_error_name_fn)(int32_t);
+
+typedef EntityId (*entity_map_fn)(EntityId);
+
+typedef const void* (*query_get_fn)(const void*, EntityId, ComponentId);
+
+typedef void* (*query_get_mut_fn)(void*, EntityId, ComponentId);
+
+typedef const void* (*query_get_first_fn)(const void*, ComponentId);
+
+typedef void* (*query_get_first_mut_fn)(void*, ComponentId);
+
+typedef void (*query_for_each_fn)(void*, QueryForEachCallback, void*);
+
+typedef void (*query_par_for_each_fn)(void*, QueryParForEachCallback, const void*);
+
+typedef void (*report_error_fn)(const char*, const char*);
+
+typedef void (*profile_begin_fn)(size_t);
+
+typedef void (*profile_end_fn)(size_t);
+
+typedef size_t (*query_len_fn)(const void*);
+
+typedef bool (*query_is_empty_fn)(const void*);
+
+typedef size_t (*query_single_fn)(void*, void**);
+
+typedef struct EntityField {
+    size_t offset;
+    size_t len;
+} EntityField;
+
+typedef struct SourceLocation {
+    const char* file;
+    uint32_t line;
+} SourceLocation;
+
+typedef struct ComponentDescriptor {
+    const char* string_id;
+    size_t size;
+    size_t align;
+    ComponentType component_type;
+    size_t entity_fields_len;
+    const EntityField* entity_fields;
+    const char* name;
+    SourceLocation source;
+} ComponentDescriptor;
+
+typedef struct QueryTermDescriptor {
+    ArgType arg_type;
+    const char* component;
+} QueryTermDescriptor;
+
+typedef struct ArgDescriptor {
+    ArgType arg_type;
+    const char* component;
+    ComponentType component_type;
+    size_t query_terms_len;
+    const QueryTermDescriptor* query_terms;
+} ArgDescriptor;
+
+typedef struct SystemDescriptor {
+    system_fn_ptr fn;
+    bool is_once;
+    size_t args_len;
+    const ArgDescriptor* args;
+    system_error_name_fn error_name;
+    const char* name;
+    SourceLocation source;
+} SystemDescriptor;
+
+typedef struct ModuleDescriptor {
+    uint32_t engine_version;
+    size_t components_len;
+    const ComponentDescriptor* components;
+    size_t systems_len;
+    const SystemDescriptor* systems;
+} ModuleDescriptor;
+
+#define ENGINE_CALLBACKS_VERSION 1
+
+typedef struct EngineCallbacks {
+    uint32_t size;
+    uint32_t version;
+    query_get_fn query_get;
+    query_get_mut_fn query_get_mut;
+    query_get_first_fn query_get_first;
+    query_get_first_mut_fn query_get_first_mut;
+    query_for_each_fn query_for_each;
+    query_par_for_each_fn query_par_for_each;
+    report_error_fn report_error;
+    profile_begin_fn profile_begin;
+    profile_end_fn profile_end;
+    query_len_fn query_len;
+    query_is_empty_fn query_is_empty;
+    query_single_fn query_single;
+} EngineCallbacks;
+
+static thread_local char LAST_ERROR[256];
+
+static ModuleStatus fail(ModuleStatus status, const char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    std::vsnprintf(LAST_ERROR, sizeof(LAST_ERROR), format, args);
+    va_end(args);
+
+    return status;
+}
+
+extern "C" const char* module_last_error() {
+    return LAST_ERROR[0] != '\0' ? LAST_ERROR : nullptr;
+}
+
+const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+const void* (*QueryGetFirstFn)(const void*, ComponentId);
+void* (*QueryGetFirstMutFn)(void*, ComponentId);
+void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+void (*ReportErrorFn)(const char*, const char*);
+void (*ProfileBeginFn)(size_t);
+void (*ProfileEndFn)(size_t);
+size_t (*QueryLenFn)(const void*);
+bool (*QueryIsEmptyFn)(const void*);
+size_t (*QuerySingleFn)(void*, void**);
+
+static void report_error(const char* context, const char* message) {
+    if (ReportErrorFn != nullptr) {
+        ReportErrorFn(context, message);
+    }
+}
+
+extern "C" ModuleStatus module_init(const EngineCallbacks* callbacks) {
+    if (callbacks == nullptr ||
+        callbacks->size < offsetof(EngineCallbacks, query_get) ||
+        callbacks->version != ENGINE_CALLBACKS_VERSION) {
+        return fail(
+            ModuleStatusUnsupportedCallbacks,
+            "unsupported engine callbacks, expected version %d",
+            ENGINE_CALLBACKS_VERSION
+        );
+    }
+
+    EngineCallbacks provided = {};
+    std::memcpy(
+        &provided,
+        callbacks,
+        callbacks->size < sizeof(EngineCallbacks) ? callbacks->size : sizeof(EngineCallbacks)
+    );
+
+    char missing[95] = "";
+    if (provided.query_get == nullptr) {
+        std::strcat(missing, " query_get");
+    }
+    if (provided.query_get_mut == nullptr) {
+        std::strcat(missing, " query_get_mut");
+    }
+    if (provided.query_get_first == nullptr) {
+        std::strcat(missing, " query_get_first");
+    }
+    if (provided.query_get_first_mut == nullptr) {
+        std::strcat(missing, " query_get_first_mut");
+    }
+    if (provided.query_for_each == nullptr) {
+        std::strcat(missing, " query_for_each");
+    }
+    if (provided.query_par_for_each == nullptr) {
+        std::strcat(missing, " query_par_for_each");
+    }
+
+    if (missing[0] != '\0') {
+        return fail(ModuleStatusMissingCallback, "the engine doesn't provide%s", missing);
+    }
+
+    QueryGetFn = provided.query_get;
+    QueryGetMutFn = provided.query_get_mut;
+    QueryGetFirstFn = provided.query_get_first;
+    QueryGetFirstMutFn = provided.query_get_first_mut;
+    QueryForEachFn = provided.query_for_each;
+    QueryParForEachFn = provided.query_par_for_each;
+    ReportErrorFn = provided.report_error;
+    ProfileBeginFn = provided.profile_begin;
+    ProfileEndFn = provided.profile_end;
+    QueryLenFn = provided.query_len;
+    QueryIsEmptyFn = provided.query_is_empty;
+    QuerySingleFn = provided.query_single;
+
+    return ModuleStatusOk;
+}
+
+static_assert(std::is_standard_layout_v<Velocity>);
+static_assert(std::is_trivially_copyable_v<Velocity>);
+static_assert(std::is_standard_layout_v<Health>);
+static_assert(std::is_trivially_copyable_v<Health>);
+
+static const ComponentDescriptor COMPONENTS[] = {
+    {
+        "game_module::Velocity",
+        sizeof(Velocity),
+        alignof(Velocity),
+        ComponentTypeComponent,
+        0,
+        nullptr,
+        "Velocity",
+        { "game.h", 5 },
+    },
+    {
+        "game_module::Health",
+        sizeof(Health),
+        alignof(Health),
+        ComponentTypeComponent,
+        0,
+        nullptr,
+        "Health",
+        { "game.h", 10 },
+    },
+};
+
+static const int32_t COMPONENT_HASH_DISPLACEMENTS[] = {
+    -2, -1,
+};
+
+static const int32_t COMPONENT_HASH_SLOTS[] = {
+    1, 0,
+};
+
+static int32_t component_index(const char* string_id) {
+    int32_t displacement = COMPONENT_HASH_DISPLACEMENTS[string_id_hash(string_id, 0) % 2];
+    uint32_t slot = displacement < 0
+        ? static_cast<uint32_t>(-displacement - 1)
+        : string_id_hash(string_id, displacement) % 2;
+    int32_t index = COMPONENT_HASH_SLOTS[slot];
+
+    if (std::strcmp(string_id, COMPONENTS[index].string_id) != 0) {
+        return -1;
+    }
+
+    return index;
+}
+
+extern "C" const char* component_string_id(size_t index) {
+    if (index >= 2) {
+        return nullptr;
+    }
+
+    return COMPONENTS[index].string_id;
+}
+
+extern "C" ModuleStatus try_component_size(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].size;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t component_size(const char* string_id) {
+    size_t value {};
+    if (try_component_size(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_size: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_align(const char* string_id, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].align;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t component_align(const char* string_id) {
+    size_t value {};
+    if (try_component_align(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_align: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_type(const char* string_id, ComponentType* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].component_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ComponentType component_type(const char* string_id) {
+    ComponentType value {};
+    if (try_component_type(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_name(const char* string_id, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].name;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* component_name(const char* string_id) {
+    const char* value {};
+    if (try_component_name(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_source_location(const char* string_id, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *out = COMPONENTS[index].source;
+    return ModuleStatusOk;
+}
+
+extern "C" SourceLocation component_source_location(const char* string_id) {
+    SourceLocation value {};
+    if (try_component_source_location(string_id, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+struct ComponentIdSlot {
+    const char* string_id;
+    ComponentId* id;
+};
+
+static const ComponentIdSlot COMPONENT_ID_SLOTS[] = {
+    { "arete_public::Camera", &Component<Camera>::ID },
+    { "arete_public::Color", &Component<Color>::ID },
+    { "game_module::Health", &Component<Health>::ID },
+    { "arete_public::Time", &Component<Time>::ID },
+    { "arete_public::Transform", &Component<Transform>::ID },
+    { "game_module::Velocity", &Component<Velocity>::ID },
+    { "arete_public::Window", &Component<Window>::ID },
+};
+
+static const int32_t COMPONENT_ID_HASH_DISPLACEMENTS[] = {
+    1, 0, -6, -4, -3, 2, 0,
+};
+
+static const int32_t COMPONENT_ID_HASH_SLOTS[] = {
+    6, 1, 0, 5, 4, 3, 2,
+};
+
+static int32_t component_id_index(const char* string_id) {
+    int32_t displacement = COMPONENT_ID_HASH_DISPLACEMENTS[string_id_hash(string_id, 0) % 7];
+    uint32_t slot = displacement < 0
+        ? static_cast<uint32_t>(-displacement - 1)
+        : string_id_hash(string_id, displacement) % 7;
+    int32_t index = COMPONENT_ID_HASH_SLOTS[slot];
+
+    if (std::strcmp(string_id, COMPONENT_ID_SLOTS[index].string_id) != 0) {
+        return -1;
+    }
+
+    return index;
+}
+
+extern "C" ModuleStatus try_set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *COMPONENT_ID_SLOTS[index].id = id;
+    return ModuleStatusOk;
+}
+
+extern "C" void set_component_id(const char* string_id, ComponentId id) {
+    int32_t index = component_id_index(string_id);
+    if (index >= 0) {
+        *COMPONENT_ID_SLOTS[index].id = id;
+    }
+}
+
+extern "C" ModuleStatus try_component_entity_fields(const char* string_id, size_t* len, const EntityField** out) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    *len = COMPONENTS[index].entity_fields_len;
+    *out = COMPONENTS[index].entity_fields;
+    return ModuleStatusOk;
+}
+
+extern "C" const EntityField* component_entity_fields(const char* string_id, size_t* len) {
+    const EntityField* value {};
+    if (try_component_entity_fields(string_id, len, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_entity_fields: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    LAST_ERROR[0] = '\0';
+
+    int32_t index = component_index(string_id);
+    if (index < 0) {
+        return fail(ModuleStatusUnknownComponent, "unknown component %s", string_id);
+    }
+
+    switch (index) {
+        default: break;
+    }
+
+    return ModuleStatusOk;
+}
+
+extern "C" void component_remap_entities(const char* string_id, void* val, entity_map_fn map_fn) {
+    if (try_component_remap_entities(string_id, val, map_fn) != ModuleStatusOk) {
+        std::fprintf(stderr, "component_remap_entities: %s\n", LAST_ERROR);
+        std::abort();
+    }
+}
+
+extern "C" ModuleStatus try_resource_init(const char* string_id, void* val, int32_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    switch (component_index(string_id)) {
+        default:
+            return fail(ModuleStatusUnknownComponent, "unknown resource %s", string_id);
+    }
+}
+
+extern "C" int32_t resource_init(const char* string_id, void* val) {
+    int32_t value {};
+    if (try_resource_init(string_id, val, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "resource_init: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+int32_t integrate_ffi(void** input) {
+    try {
+        integrate(
+            { input[0] },
+            *static_cast<const Time*>(input[1])
+        );
+
+        return 0;
+    } catch (const std::exception& e) {
+        report_error("integrate", e.what());
+        return -1;
+    } catch (...) {
+        report_error("integrate", "unknown exception");
+        return -1;
+    }
+}
+
+int32_t render_health_ffi(void** input) {
+    try {
+        render_health(
+            { input[0] },
+            { input[1] },
+            *static_cast<const Window*>(input[2])
+        );
+
+        return 0;
+    } catch (const std::exception& e) {
+        report_error("render_health", e.what());
+        return -1;
+    } catch (...) {
+        report_error("render_health", "unknown exception");
+        return -1;
+    }
+}
+
+static const QueryTermDescriptor integrate_ARG_0_TERMS[] = {
+    { ArgTypeDataAccessMut, "arete_public::Transform" },
+    { ArgTypeDataAccessRef, "game_module::Velocity" },
+};
+
+static const ArgDescriptor integrate_ARGS[] = {
+    { ArgTypeQuery, nullptr, ComponentTypeComponent, 2, integrate_ARG_0_TERMS },
+    { ArgTypeDataAccessRef, "arete_public::Time", ComponentTypeResource, 0, nullptr },
+};
+
+static const QueryTermDescriptor render_health_ARG_0_TERMS[] = {
+    { ArgTypeDataAccessRef, "arete_public::Transform" },
+    { ArgTypeDataAccessRef, "game_module::Health" },
+    { ArgTypeDataAccessMut, "arete_public::Color" },
+};
+
+static const QueryTermDescriptor render_health_ARG_1_TERMS[] = {
+    { ArgTypeDataAccessRef, "arete_public::Camera" },
+};
+
+static const ArgDescriptor render_health_ARGS[] = {
+    { ArgTypeQuery, nullptr, ComponentTypeComponent, 3, render_health_ARG_0_TERMS },
+    { ArgTypeQuery, nullptr, ComponentTypeComponent, 1, render_health_ARG_1_TERMS },
+    { ArgTypeDataAccessRef, "arete_public::Window", ComponentTypeResource, 0, nullptr },
+};
+
+static const SystemDescriptor SYSTEMS[] = {
+    { integrate_ffi, false, 2, integrate_ARGS, nullptr, "integrate", { "game.h", 15 } },
+    { render_health_ffi, false, 3, render_health_ARGS, nullptr, "render_health", { "game.h", 16 } },
+};
+
+extern "C" size_t systems_len() {
+    return 2;
+}
+
+extern "C" ModuleStatus try_system_name(size_t system_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].name;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_name(size_t system_index) {
+    const char* value {};
+    if (try_system_name(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_source_location(size_t system_index, SourceLocation* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].source;
+    return ModuleStatusOk;
+}
+
+extern "C" SourceLocation system_source_location(size_t system_index) {
+    SourceLocation value {};
+    if (try_system_source_location(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_source_location: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_is_once(size_t system_index, bool* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].is_once;
+    return ModuleStatusOk;
+}
+
+extern "C" bool system_is_once(size_t system_index) {
+    bool value {};
+    if (try_system_is_once(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_is_once: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_fn(size_t system_index, system_fn_ptr* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].fn;
+    return ModuleStatusOk;
+}
+
+extern "C" system_fn_ptr system_fn(size_t system_index) {
+    system_fn_ptr value {};
+    if (try_system_fn(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_fn: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_error_name(size_t system_index, int32_t code, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    *out = system.error_name != nullptr ? system.error_name(code) : nullptr;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_error_name(size_t system_index, int32_t code) {
+    const char* value {};
+    if (try_system_error_name(system_index, code, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_error_name: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_args_len(size_t system_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    *out = SYSTEMS[system_index].args_len;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t system_args_len(size_t system_index) {
+    size_t value {};
+    if (try_system_args_len(system_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_type(size_t system_index, size_t arg_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    *out = system.args[arg_index].arg_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ArgType system_arg_type(size_t system_index, size_t arg_index) {
+    ArgType value {};
+    if (try_system_arg_type(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_arg_component(size_t system_index, size_t arg_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    if (system.args[arg_index].component == nullptr) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is a query", arg_index, system_index);
+    }
+
+    *out = system.args[arg_index].component;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_arg_component(size_t system_index, size_t arg_index) {
+    const char* value {};
+    if (try_system_arg_component(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_args_len(size_t system_index, size_t arg_index, size_t* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    if (system.args[arg_index].arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    *out = system.args[arg_index].query_terms_len;
+    return ModuleStatusOk;
+}
+
+extern "C" size_t system_query_args_len(size_t system_index, size_t arg_index) {
+    size_t value {};
+    if (try_system_query_args_len(system_index, arg_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_args_len: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index, ArgType* out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    const ArgDescriptor& arg = system.args[arg_index];
+    if (arg.arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    if (query_index >= arg.query_terms_len) {
+        return fail(ModuleStatusUnknownQueryTerm, "argument %zu of system %zu has no term %zu", arg_index, system_index, query_index);
+    }
+
+    *out = arg.query_terms[query_index].arg_type;
+    return ModuleStatusOk;
+}
+
+extern "C" ArgType system_query_arg_type(size_t system_index, size_t arg_index, size_t query_index) {
+    ArgType value {};
+    if (try_system_query_arg_type(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_type: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+extern "C" ModuleStatus try_system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index, const char** out) {
+    LAST_ERROR[0] = '\0';
+
+    if (system_index >= 2) {
+        return fail(ModuleStatusUnknownSystem, "unknown system %zu", system_index);
+    }
+
+    const SystemDescriptor& system = SYSTEMS[system_index];
+    if (arg_index >= system.args_len) {
+        return fail(ModuleStatusUnknownArg, "system %zu has no argument %zu", system_index, arg_index);
+    }
+
+    const ArgDescriptor& arg = system.args[arg_index];
+    if (arg.arg_type != ArgTypeQuery) {
+        return fail(ModuleStatusWrongArgType, "argument %zu of system %zu is not a query", arg_index, system_index);
+    }
+
+    if (query_index >= arg.query_terms_len) {
+        return fail(ModuleStatusUnknownQueryTerm, "argument %zu of system %zu has no term %zu", arg_index, system_index, query_index);
+    }
+
+    *out = arg.query_terms[query_index].component;
+    return ModuleStatusOk;
+}
+
+extern "C" const char* system_query_arg_component(size_t system_index, size_t arg_index, size_t query_index) {
+    const char* value {};
+    if (try_system_query_arg_component(system_index, arg_index, query_index, &value) != ModuleStatusOk) {
+        std::fprintf(stderr, "system_query_arg_component: %s\n", LAST_ERROR);
+        std::abort();
+    }
+
+    return value;
+}
+
+static const ModuleDescriptor MODULE_DESCRIPTOR = {
+    ENGINE_VERSION,
+    2,
+    COMPONENTS,
+    2,
+    SYSTEMS,
+};
+
+extern "C" const ModuleDescriptor* arete_module_descriptor() {
+    return &MODULE_DESCRIPTOR;
+}
+
+static constexpr uint64_t compute_abi_hash() {
+    uint64_t hash = 0x90873804291ed27dull;
+
+    const uint64_t layouts[] = {
+        sizeof(Camera),
+        alignof(Camera),
+        sizeof(Color),
+        alignof(Color),
+        sizeof(Time),
+        alignof(Time),
+        sizeof(Transform),
+        alignof(Transform),
+        sizeof(Window),
+        alignof(Window),
+    };
+
+    for (uint64_t value : layouts) {
+        for (int i = 0; i < 8; ++i) {
+            hash ^= (value >> (i * 8)) & 0xff;
+            hash *= 0x100000001b3ull;
+        }
+    }
+
+    return hash;
+}
+
+extern "C" uint64_t arete_abi_hash() {
+    constexpr uint64_t hash = compute_abi_hash();
+    return hash;
+}
+
@@ -0,0 +1,19 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z;
+};
+COMPONENT(Velocity);
+
+struct Health {
+    int32_t current, max;
+};
+COMPONENT(Health);
+
+SYSTEM(void integrate, Query<Transform&, const Velocity&> movers, const Time& time);
+SYSTEM(void render_health,
+       Query<const Transform&, const Health&, Color&> bars,
+       Query<const Camera&> cameras,
+       const Window& window);
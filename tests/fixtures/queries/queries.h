@@ -0,0 +1,345 @@
+#pragma once
+
+#include "game.h"
+#include <cstddef>
+#include <cstdint>
+#include <cstdio>
+#include <optional>
+#include <tuple>
+
+// defined in ffi.cpp, and set by the engine through module_init
+extern const void* (*QueryGetFn)(const void*, EntityId, ComponentId);
+extern void* (*QueryGetMutFn)(void*, EntityId, ComponentId);
+extern const void* (*QueryGetFirstFn)(const void*, ComponentId);
+extern void* (*QueryGetFirstMutFn)(void*, ComponentId);
+extern void (*QueryForEachFn)(void*, QueryForEachCallback, void*);
+extern void (*QueryParForEachFn)(void*, QueryParForEachCallback, const void*);
+extern void (*ReportErrorFn)(const char*, const char*);
+extern void (*ProfileBeginFn)(size_t);
+extern void (*ProfileEndFn)(size_t);
+extern size_t (*QueryLenFn)(const void*);
+extern bool (*QueryIsEmptyFn)(const void*);
+extern size_t (*QuerySingleFn)(void*, void**);
+
+template <typename... Ts>
+struct TypedQuery;
+
+// `TypedQuery movers(query);` picks the specialization for the system's argument
+template <typename... Ts>
+TypedQuery(Query<Ts...>) -> TypedQuery<Ts...>;
+
+template <>
+struct TypedQuery<Transform&, const Velocity&> {
+    using Item = std::tuple<Transform&, const Velocity&>;
+
+    void* handle;
+
+    explicit TypedQuery(Query<Transform&, const Velocity&> query) : handle(query.handle) {}
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            (*static_cast<F*>(user_data))(
+                *static_cast<Transform*>(components[0]),
+                *static_cast<const Velocity*>(components[1])
+            );
+        };
+
+        QueryForEachFn(handle, callback, &f);
+    }
+
+    template <typename F>
+    void par_for_each(const F& f) {
+        QueryParForEachCallback callback = [](const void* user_data, void** components) {
+            (*static_cast<const F*>(user_data))(
+                *static_cast<Transform*>(components[0]),
+                *static_cast<const Velocity*>(components[1])
+            );
+        };
+
+        QueryParForEachFn(handle, callback, &f);
+    }
+
+    std::optional<Item> get(EntityId entity) {
+        Transform* term_0 = static_cast<Transform*>(QueryGetMutFn(handle, entity, Component<Transform>::ID));
+        const Velocity* term_1 = static_cast<const Velocity*>(QueryGetFn(handle, entity, Component<Velocity>::ID));
+
+        if (term_0 == nullptr || term_1 == nullptr) {
+            return std::nullopt;
+        }
+
+        return Item(*term_0, *term_1);
+    }
+
+    size_t len() const {
+        if (QueryLenFn == nullptr) {
+            return count(nullptr);
+        }
+
+        return QueryLenFn(handle);
+    }
+
+    bool is_empty() const {
+        if (QueryIsEmptyFn == nullptr) {
+            return count(nullptr) == 0;
+        }
+
+        return QueryIsEmptyFn(handle);
+    }
+
+    std::optional<Item> single() {
+        void* components[2] = {};
+        size_t matches = QuerySingleFn != nullptr
+            ? QuerySingleFn(handle, components)
+            : count(components);
+
+        if (matches != 1) {
+            if (ReportErrorFn != nullptr) {
+                char message[64];
+                std::snprintf(message, sizeof(message), "expected one match, found %zu", matches);
+                ReportErrorFn("TypedQuery::single", message);
+            }
+
+            return std::nullopt;
+        }
+
+        return Item(
+            *static_cast<Transform*>(components[0]),
+            *static_cast<const Velocity*>(components[1])
+        );
+    }
+
+private:
+    size_t count(void** first) const {
+        struct Counter {
+            size_t matches;
+            void** first;
+        };
+
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            Counter* counter = static_cast<Counter*>(user_data);
+            if (counter->matches == 0 && counter->first != nullptr) {
+                for (size_t i = 0; i < 2; ++i) {
+                    counter->first[i] = components[i];
+                }
+            }
+
+            ++counter->matches;
+        };
+
+        Counter counter = { 0, first };
+        QueryForEachFn(handle, callback, &counter);
+        return counter.matches;
+    }
+};
+
+template <>
+struct TypedQuery<const Transform&, const Health&, Color&> {
+    using Item = std::tuple<const Transform&, const Health&, Color&>;
+
+    void* handle;
+
+    explicit TypedQuery(Query<const Transform&, const Health&, Color&> query) : handle(query.handle) {}
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            (*static_cast<F*>(user_data))(
+                *static_cast<const Transform*>(components[0]),
+                *static_cast<const Health*>(components[1]),
+                *static_cast<Color*>(components[2])
+            );
+        };
+
+        QueryForEachFn(handle, callback, &f);
+    }
+
+    template <typename F>
+    void par_for_each(const F& f) {
+        QueryParForEachCallback callback = [](const void* user_data, void** components) {
+            (*static_cast<const F*>(user_data))(
+                *static_cast<const Transform*>(components[0]),
+                *static_cast<const Health*>(components[1]),
+                *static_cast<Color*>(components[2])
+            );
+        };
+
+        QueryParForEachFn(handle, callback, &f);
+    }
+
+    std::optional<Item> get(EntityId entity) {
+        const Transform* term_0 = static_cast<const Transform*>(QueryGetFn(handle, entity, Component<Transform>::ID));
+        const Health* term_1 = static_cast<const Health*>(QueryGetFn(handle, entity, Component<Health>::ID));
+        Color* term_2 = static_cast<Color*>(QueryGetMutFn(handle, entity, Component<Color>::ID));
+
+        if (term_0 == nullptr || term_1 == nullptr || term_2 == nullptr) {
+            return std::nullopt;
+        }
+
+        return Item(*term_0, *term_1, *term_2);
+    }
+
+    size_t len() const {
+        if (QueryLenFn == nullptr) {
+            return count(nullptr);
+        }
+
+        return QueryLenFn(handle);
+    }
+
+    bool is_empty() const {
+        if (QueryIsEmptyFn == nullptr) {
+            return count(nullptr) == 0;
+        }
+
+        return QueryIsEmptyFn(handle);
+    }
+
+    std::optional<Item> single() {
+        void* components[3] = {};
+        size_t matches = QuerySingleFn != nullptr
+            ? QuerySingleFn(handle, components)
+            : count(components);
+
+        if (matches != 1) {
+            if (ReportErrorFn != nullptr) {
+                char message[64];
+                std::snprintf(message, sizeof(message), "expected one match, found %zu", matches);
+                ReportErrorFn("TypedQuery::single", message);
+            }
+
+            return std::nullopt;
+        }
+
+        return Item(
+            *static_cast<const Transform*>(components[0]),
+            *static_cast<const Health*>(components[1]),
+            *static_cast<Color*>(components[2])
+        );
+    }
+
+private:
+    size_t count(void** first) const {
+        struct Counter {
+            size_t matches;
+            void** first;
+        };
+
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            Counter* counter = static_cast<Counter*>(user_data);
+            if (counter->matches == 0 && counter->first != nullptr) {
+                for (size_t i = 0; i < 3; ++i) {
+                    counter->first[i] = components[i];
+                }
+            }
+
+            ++counter->matches;
+        };
+
+        Counter counter = { 0, first };
+        QueryForEachFn(handle, callback, &counter);
+        return counter.matches;
+    }
+};
+
+template <>
+struct TypedQuery<const Camera&> {
+    using Item = std::tuple<const Camera&>;
+
+    void* handle;
+
+    explicit TypedQuery(Query<const Camera&> query) : handle(query.handle) {}
+
+    template <typename F>
+    void for_each(F f) {
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            (*static_cast<F*>(user_data))(
+                *static_cast<const Camera*>(components[0])
+            );
+        };
+
+        QueryForEachFn(handle, callback, &f);
+    }
+
+    template <typename F>
+    void par_for_each(const F& f) {
+        QueryParForEachCallback callback = [](const void* user_data, void** components) {
+            (*static_cast<const F*>(user_data))(
+                *static_cast<const Camera*>(components[0])
+            );
+        };
+
+        QueryParForEachFn(handle, callback, &f);
+    }
+
+    std::optional<Item> get(EntityId entity) {
+        const Camera* term_0 = static_cast<const Camera*>(QueryGetFn(handle, entity, Component<Camera>::ID));
+
+        if (term_0 == nullptr) {
+            return std::nullopt;
+        }
+
+        return Item(*term_0);
+    }
+
+    size_t len() const {
+        if (QueryLenFn == nullptr) {
+            return count(nullptr);
+        }
+
+        return QueryLenFn(handle);
+    }
+
+    bool is_empty() const {
+        if (QueryIsEmptyFn == nullptr) {
+            return count(nullptr) == 0;
+        }
+
+        return QueryIsEmptyFn(handle);
+    }
+
+    std::optional<Item> single() {
+        void* components[1] = {};
+        size_t matches = QuerySingleFn != nullptr
+            ? QuerySingleFn(handle, components)
+            : count(components);
+
+        if (matches != 1) {
+            if (ReportErrorFn != nullptr) {
+                char message[64];
+                std::snprintf(message, sizeof(message), "expected one match, found %zu", matches);
+                ReportErrorFn("TypedQuery::single", message);
+            }
+
+            return std::nullopt;
+        }
+
+        return Item(
+            *static_cast<const Camera*>(components[0])
+        );
+    }
+
+private:
+    size_t count(void** first) const {
+        struct Counter {
+            size_t matches;
+            void** first;
+        };
+
+        QueryForEachCallback callback = [](void* user_data, void** components) {
+            Counter* counter = static_cast<Counter*>(user_data);
+            if (counter->matches == 0 && counter->first != nullptr) {
+                for (size_t i = 0; i < 1; ++i) {
+                    counter->first[i] = components[i];
+                }
+            }
+
+            ++counter->matches;
+        };
+
+        Counter counter = { 0, first };
+        QueryForEachFn(handle, callback, &counter);
+        return counter.matches;
+    }
+};
+
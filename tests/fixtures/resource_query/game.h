@@ -0,0 +1,10 @@
+#pragma once
+
+#include "arete.h"
+
+struct Score {
+    int value;
+};
+RESOURCE(Score);
+
+SYSTEM(void tally, Query<Score&> scores);
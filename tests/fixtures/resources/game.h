@@ -0,0 +1,18 @@
+#pragma once
+
+#include "arete.h"
+
+struct Score {
+    int value;
+    EntityId best_player;
+};
+RESOURCE(Score);
+
+class Settings {
+public:
+    float volume = 1.0f;
+    bool paused;
+};
+RESOURCE(Settings);
+
+SYSTEM(void tick, Score& score, const Settings& settings, const Time& time);
@@ -0,0 +1,10 @@
+#pragma once
+
+#include "arete.h"
+
+// Velocity is neither declared with COMPONENT nor public
+struct Velocity {
+    float x, y, z;
+};
+
+SYSTEM(void integrate, Query<Transform&, const Velocity&> movers);
@@ -0,0 +1,156 @@
+// Every directory under tests/fixtures holds a module header, `game.h`, and the output expected from
+// it against the mock engine's catalogue: one file per backend when the module is valid, `error.txt`
+// when it fails to parse or validate.
+// Run with `UPDATE_GOLDEN=1` to rewrite the expectations after an intended change, then review the
+// diff.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use arete_codegen::{Backend, CppOptions, EngineCatalogue};
+
+fn check_fixture(name: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = root.join("tests/fixtures").join(name);
+    let catalogue = EngineCatalogue::load(&root.join("mock/engine.json")).unwrap();
+
+    let source = fs::read_to_string(dir.join("game.h")).unwrap();
+    let module = arete_codegen::parse_source(&source, "game.h", catalogue)
+        .and_then(|module| module.validate().map(|()| module));
+
+    let module = match module {
+        Ok(module) => module,
+        Err(err) => return check_output(name, &dir.join("error.txt"), format!("{err}\n")),
+    };
+
+    let include = String::from("game.h");
+    let backends = [
+        (
+            "ffi.cpp",
+            Backend::Cpp {
+                include: include.clone(),
+                options: CppOptions::default(),
+            },
+        ),
+        ("ffi.h", Backend::CHeader),
+        ("bindings.rs", Backend::RustBindings),
+        ("manifest.json", Backend::Manifest),
+        (
+            "queries.h",
+            Backend::Queries {
+                include: include.clone(),
+            },
+        ),
+        (
+            "harness.h",
+            Backend::TestHarness {
+                include,
+                header: String::from("ffi.h"),
+            },
+        ),
+    ];
+
+    for (file, backend) in backends {
+        check_output(name, &dir.join(file), module.generate(&backend).unwrap());
+    }
+}
+
+fn check_output(name: &str, expected_path: &Path, actual: String) {
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(expected_path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(expected_path).unwrap_or_else(|_| {
+        panic!(
+            "{} is missing, run with UPDATE_GOLDEN=1 to create it",
+            expected_path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "output for fixture {name} differs from {}, run with UPDATE_GOLDEN=1 to update it",
+        expected_path.display()
+    );
+}
+
+#[test]
+fn components() {
+    check_fixture("components");
+}
+
+#[test]
+fn resources() {
+    check_fixture("resources");
+}
+
+#[test]
+fn once_systems() {
+    check_fixture("once_systems");
+}
+
+#[test]
+fn queries() {
+    check_fixture("queries");
+}
+
+#[test]
+fn errors() {
+    check_fixture("errors");
+}
+
+#[test]
+fn empty() {
+    check_fixture("empty");
+}
+
+#[test]
+fn resource_query() {
+    check_fixture("resource_query");
+}
+
+#[test]
+fn direct_component() {
+    check_fixture("direct_component");
+}
+
+#[test]
+fn undeclared_component() {
+    check_fixture("undeclared_component");
+}
+
+#[test]
+fn by_value_param() {
+    check_fixture("by_value_param");
+}
+
+#[test]
+fn unterminated_query() {
+    check_fixture("unterminated_query");
+}
+
+#[test]
+fn duplicate_component() {
+    check_fixture("duplicate_component");
+}
+
+// an engine header that doesn't declare everything in the catalogue still builds the module
+#[test]
+fn only_used_engine_types() {
+    let source = "struct Velocity { float x; };\nCOMPONENT(Velocity);\n\
+                  SYSTEM(void integrate, Query<Transform&, const Velocity&> movers);\n";
+    let module = arete_codegen::parse_source(source, "game.h", EngineCatalogue::default()).unwrap();
+
+    let ffi = module
+        .generate(&Backend::Cpp {
+            include: String::from("game.h"),
+            options: CppOptions::default(),
+        })
+        .unwrap();
+    assert!(ffi.contains("Component<Transform>::ID"));
+    for unused in ["Camera", "Color", "PointLight", "Time", "Window"] {
+        assert!(!ffi.contains(unused), "ffi.cpp refers to {unused}");
+    }
+}
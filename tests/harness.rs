@@ -0,0 +1,89 @@
+// Builds the module in tests/harness together with a C++ test written against the generated test
+// harness, and runs it. Like the compile tests, skipped when no compiler is found unless
+// `ARETE_REQUIRE_CXX` is set.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use arete_codegen::{Backend, CppOptions, EngineCatalogue};
+
+mod common;
+
+#[test]
+fn systems_run_against_fake_world() {
+    let Some(cxx) = common::compiler() else {
+        return;
+    };
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let module_dir = root.join("tests/harness");
+    let catalogue = EngineCatalogue::load(&root.join("mock/engine.json")).unwrap();
+
+    let source = fs::read_to_string(module_dir.join("game.h")).unwrap();
+    let module = arete_codegen::parse_source(&source, "game.h", catalogue).unwrap();
+
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("harness");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let ffi = module.generate(&Backend::Cpp {
+        include: String::from("game.h"),
+        options: CppOptions {
+            profile: true,
+            ..CppOptions::default()
+        },
+    });
+    let harness = module.generate(&Backend::TestHarness {
+        include: String::from("game.h"),
+        header: String::from("ffi.h"),
+    });
+
+    fs::write(out_dir.join("ffi.cpp"), ffi.unwrap()).unwrap();
+    fs::write(
+        out_dir.join("ffi.h"),
+        module.generate(&Backend::CHeader).unwrap(),
+    )
+    .unwrap();
+    fs::write(out_dir.join("harness.h"), harness.unwrap()).unwrap();
+    fs::write(
+        out_dir.join("queries.h"),
+        module
+            .generate(&Backend::Queries {
+                include: String::from("game.h"),
+            })
+            .unwrap(),
+    )
+    .unwrap();
+
+    let exe = out_dir.join("test");
+
+    let output = Command::new(&cxx)
+        .args(["-std=c++20", "-Wall", "-Werror"])
+        .arg("-I")
+        .arg(&module_dir)
+        .arg("-I")
+        .arg(root.join("mock"))
+        .arg("-I")
+        .arg(&out_dir)
+        .arg(out_dir.join("ffi.cpp"))
+        .arg(module_dir.join("game.cpp"))
+        .arg(module_dir.join("test.cpp"))
+        .arg("-o")
+        .arg(&exe)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "test harness failed to build:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&exe).output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "harness test failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,44 @@
+#include "game.h"
+#include "queries.h"
+
+#include <stdexcept>
+
+void start(Stats& stats) {
+    stats.started += 1;
+}
+
+void integrate(Query<Transform&, const Velocity&> movers, Stats& stats, const Time& time) {
+    stats.frames += 1;
+
+    movers.for_each([&](Transform& transform, const Velocity& velocity) {
+        transform.position[0] += velocity.x * time.delta;
+        transform.position[1] += velocity.y * time.delta;
+        transform.position[2] += velocity.z * time.delta;
+        stats.moved += 1;
+    });
+}
+
+void check_frames(const Stats& stats) {
+    if (stats.frames > 2) {
+        throw std::runtime_error("ran too many frames");
+    }
+}
+
+void inspect(Query<const Transform&, const Velocity&> query, Stats& stats) {
+    TypedQuery movers(query);
+
+    if (auto tracked = movers.get(stats.tracked)) {
+        auto& [transform, velocity] = *tracked;
+        stats.tracked_x = transform.position[0];
+    }
+
+    if (auto single = movers.single()) {
+        stats.single_x = std::get<0>(*single).position[0];
+    }
+
+    movers.par_for_each([&](const Transform&, const Velocity&) {
+        stats.inspected += 1;
+    });
+
+    stats.movers = movers.is_empty() ? 0 : static_cast<uint32_t>(movers.len());
+}
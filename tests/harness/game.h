@@ -0,0 +1,26 @@
+#pragma once
+
+#include "arete.h"
+
+struct Velocity {
+    float x, y, z;
+};
+COMPONENT(Velocity);
+
+struct Stats {
+    uint32_t frames;
+    uint32_t moved;
+    uint32_t started;
+    // set by the test, for `inspect` to look up
+    EntityId tracked;
+    float tracked_x;
+    float single_x;
+    uint32_t inspected;
+    uint32_t movers;
+};
+RESOURCE(Stats);
+
+SYSTEM_ONCE(void start, Stats& stats);
+SYSTEM(void integrate, Query<Transform&, const Velocity&> movers, Stats& stats, const Time& time);
+SYSTEM(void check_frames, const Stats& stats);
+SYSTEM(void inspect, Query<const Transform&, const Velocity&> query, Stats& stats);
@@ -0,0 +1,108 @@
+#include "harness.h"
+
+#include <cstdio>
+
+#define CHECK(cond)                                                     \
+    if (!(cond)) {                                                      \
+        std::fprintf(stderr, "%s:%d: %s failed\n", __FILE__, __LINE__, #cond); \
+        return 1;                                                       \
+    }
+
+static size_t profile_begins[8];
+static size_t profile_ends[8];
+
+static void profile_begin(size_t system_index) {
+    profile_begins[system_index] += 1;
+}
+
+static void profile_end(size_t system_index) {
+    profile_ends[system_index] += 1;
+}
+
+int main() {
+    arete_test::World world;
+
+    EngineCallbacks callbacks = arete_test::World::engine_callbacks();
+    callbacks.profile_begin = &profile_begin;
+    callbacks.profile_end = &profile_end;
+    CHECK(module_init(&callbacks) == ModuleStatusOk);
+
+    EntityId mover = world.spawn(Transform {}, Velocity { 1.0f, 2.0f, 0.0f });
+    // without a velocity it doesn't match the query
+    EntityId still = world.spawn(Transform {});
+
+    world.insert_resource(Time { 0.5f, 0.0 });
+
+    CHECK(arete_test::invoke_start(world) == 0);
+    CHECK(arete_test::invoke_integrate(world) == 0);
+    CHECK(arete_test::invoke_integrate(world) == 0);
+
+    CHECK(world.resource<Stats>().started == 1);
+    CHECK(world.resource<Stats>().frames == 2);
+    CHECK(world.resource<Stats>().moved == 2);
+
+    CHECK(world.get<Transform>(mover)->position[0] == 1.0f);
+    CHECK(world.get<Transform>(mover)->position[1] == 2.0f);
+    CHECK(world.get<Transform>(still)->position[0] == 0.0f);
+    CHECK(world.get<Velocity>(still) == nullptr);
+    CHECK(world.entities_len() == 2);
+
+    // exceptions are caught at the boundary and reported
+    CHECK(arete_test::invoke_check_frames(world) == 0);
+    CHECK(world.last_error().empty());
+    CHECK(arete_test::invoke_integrate(world) == 0);
+    CHECK(arete_test::invoke_check_frames(world) == -1);
+    CHECK(world.last_error() == "check_frames: ran too many frames");
+
+    // typed queries only see entities matching every term
+    world.resource<Stats>().tracked = still;
+    CHECK(arete_test::invoke_inspect(world) == 0);
+    CHECK(world.resource<Stats>().tracked_x == 0.0f);
+    CHECK(world.resource<Stats>().single_x == 1.5f);
+    CHECK(world.resource<Stats>().inspected == 1);
+    CHECK(world.resource<Stats>().movers == 1);
+
+    world.resource<Stats>().tracked = mover;
+    world.spawn(Transform {}, Velocity {});
+    CHECK(arete_test::invoke_inspect(world) == 0);
+    CHECK(world.resource<Stats>().tracked_x == 1.5f);
+    CHECK(world.resource<Stats>().inspected == 3);
+    CHECK(world.resource<Stats>().movers == 2);
+    // with two movers there's no single one, and the engine is told why
+    CHECK(world.last_error() == "TypedQuery::single: expected one match, found 2");
+
+    // every run is profiled, including the one that threw
+    CHECK(profile_begins[1] == 3 && profile_ends[1] == 3);
+    CHECK(profile_begins[2] == 2 && profile_ends[2] == 2);
+
+    // the module's systems take queries, so the query callbacks can't be left out
+    EngineCallbacks missing = {};
+    missing.size = sizeof(EngineCallbacks);
+    missing.version = ENGINE_CALLBACKS_VERSION;
+    CHECK(module_init(&missing) == ModuleStatusMissingCallback);
+    CHECK(module_last_error() != nullptr);
+
+    missing.size = 4;
+    CHECK(module_init(&missing) == ModuleStatusUnsupportedCallbacks);
+
+    // an engine from before the counting callbacks falls back to iterating
+    arete_test::World older;
+    EngineCallbacks older_callbacks = arete_test::World::engine_callbacks();
+    older_callbacks.size = offsetof(EngineCallbacks, query_len);
+    CHECK(module_init(&older_callbacks) == ModuleStatusOk);
+
+    older.spawn(Transform { { 3.0f, 0.0f, 0.0f } }, Velocity {});
+    older.spawn(Transform {});
+    older.insert_resource(Stats {});
+    CHECK(arete_test::invoke_inspect(older) == 0);
+    CHECK(older.resource<Stats>().single_x == 3.0f);
+    CHECK(older.resource<Stats>().movers == 1);
+    CHECK(older.last_error().empty());
+
+    older.spawn(Transform {}, Velocity {});
+    CHECK(arete_test::invoke_inspect(older) == 0);
+    CHECK(older.resource<Stats>().movers == 2);
+    CHECK(older.last_error() == "TypedQuery::single: expected one match, found 2");
+
+    return 0;
+}
@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::Path;
+
+use arete_codegen::{
+    ArgType, Backend, EngineCatalogue, Error, FfiGenerator, ReturnType, StructInfo, StructType,
+    SystemInfo,
+};
+
+fn try_parse_fixture(name: &str) -> Result<FfiGenerator, Error> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    try_parse_fixture_with(
+        name,
+        EngineCatalogue::load(&root.join("mock/engine.json")).unwrap(),
+    )
+}
+
+fn try_parse_fixture_with(name: &str, catalogue: EngineCatalogue) -> Result<FfiGenerator, Error> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .join("game.h");
+
+    let source = fs::read_to_string(path).unwrap();
+    arete_codegen::parse_source(&source, "game.h", catalogue)
+}
+
+fn parse_fixture(name: &str) -> FfiGenerator {
+    try_parse_fixture(name).unwrap()
+}
+
+// (line, message) of a parse error
+fn parse_error(name: &str) -> (usize, String) {
+    match try_parse_fixture(name) {
+        Err(Error::Parse { source, message }) => (source.line, message),
+        other => panic!("fixture {name} didn't fail to parse: {other:?}"),
+    }
+}
+
+fn find_struct<'a>(module: &'a FfiGenerator, ident: &str) -> &'a StructInfo {
+    module.structs.iter().find(|s| s.ident == ident).unwrap()
+}
+
+fn find_system<'a>(module: &'a FfiGenerator, ident: &str) -> &'a SystemInfo {
+    module.systems.iter().find(|s| s.ident == ident).unwrap()
+}
+
+// (ident, type, array length) for each field
+fn fields(struct_info: &StructInfo) -> Vec<(&str, &str, Option<&str>)> {
+    struct_info
+        .fields
+        .iter()
+        .map(|f| (f.ident.as_str(), f.ty.as_str(), f.array_len.as_deref()))
+        .collect()
+}
+
+// (ident, mutable) for each term of the query at `index`
+fn query_terms(system: &SystemInfo, index: usize) -> Vec<(&str, bool)> {
+    let ArgType::Query { inputs } = &system.inputs[index].arg_type else {
+        panic!("argument {index} of {} isn't a query", system.ident);
+    };
+
+    inputs
+        .iter()
+        .map(|input| (input.ident.as_str(), input.mutable))
+        .collect()
+}
+
+#[test]
+fn components() {
+    let module = parse_fixture("components");
+
+    let idents: Vec<_> = module.structs.iter().map(|s| s.ident.as_str()).collect();
+    assert_eq!(idents, ["Velocity", "Follow", "Tint", "Packet"]);
+    assert!(module.systems.is_empty());
+
+    let velocity = find_struct(&module, "Velocity");
+    assert_eq!(velocity.struct_type, StructType::Component);
+    assert_eq!(velocity.string_id, "game_module::Velocity");
+    assert_eq!(velocity.source.line, 5);
+    assert_eq!(
+        fields(velocity),
+        [
+            ("x", "float", None),
+            ("y", "float", None),
+            ("z", "float", None)
+        ]
+    );
+
+    let follow = find_struct(&module, "Follow");
+    assert_eq!(follow.source.line, 14);
+    assert!(follow.has_entity_fields());
+    // member functions don't take up space
+    assert!(!follow.has_skipped_members);
+    assert_eq!(
+        fields(follow),
+        [
+            ("target", "EntityId", None),
+            ("trail", "EntityId", Some("4")),
+            ("label", "const char*", None),
+            ("flags", "int", None),
+        ]
+    );
+
+    let tint = find_struct(&module, "Tint");
+    assert!(!tint.has_entity_fields());
+    assert_eq!(
+        fields(tint),
+        [
+            ("color", "Color", None),
+            ("amount", "float", None),
+            ("channels", "uint8_t", Some("3")),
+            ("grade", "char", None),
+        ]
+    );
+    assert!(!tint.has_skipped_members);
+
+    // only the plain field is parsed, but the layout is known to be incomplete
+    let packet = find_struct(&module, "Packet");
+    assert_eq!(fields(packet), [("sequence", "uint32_t", None)]);
+    assert!(packet.has_skipped_members);
+}
+
+#[test]
+fn resources() {
+    let module = parse_fixture("resources");
+
+    let score = find_struct(&module, "Score");
+    assert_eq!(score.struct_type, StructType::Resource);
+    assert_eq!(
+        fields(score),
+        [("value", "int", None), ("best_player", "EntityId", None)]
+    );
+
+    let settings = find_struct(&module, "Settings");
+    assert_eq!(settings.struct_type, StructType::Resource);
+    assert_eq!(
+        fields(settings),
+        [("volume", "float", None), ("paused", "bool", None)]
+    );
+
+    let tick = find_system(&module, "tick");
+    assert!(!tick.is_once);
+    assert_eq!(tick.source.line, 18);
+
+    let inputs: Vec<_> = tick
+        .inputs
+        .iter()
+        .map(|input| (input.ident.as_str(), input.mutable))
+        .collect();
+    assert_eq!(
+        inputs,
+        [("Score", true), ("Settings", false), ("Time", false)]
+    );
+    assert!(tick
+        .inputs
+        .iter()
+        .all(|input| matches!(input.arg_type, ArgType::DataAccessDirect)));
+
+    assert!(module.validate().is_ok());
+}
+
+// the built-in catalogue only knows the engine's components, its resources need a manifest
+#[test]
+fn resources_without_engine_manifest() {
+    let module = try_parse_fixture_with("resources", EngineCatalogue::default()).unwrap();
+
+    match module.validate() {
+        Err(Error::Invalid { message, .. }) => assert_eq!(
+            message,
+            "system tick takes Time, which is neither declared by the module nor in the engine catalogue"
+        ),
+        other => panic!("resources validated without an engine manifest: {other:?}"),
+    }
+}
+
+#[test]
+fn once_systems() {
+    let module = parse_fixture("once_systems");
+
+    let systems: Vec<_> = module
+        .systems
+        .iter()
+        .map(|s| (s.ident.as_str(), s.is_once))
+        .collect();
+    assert_eq!(
+        systems,
+        [("setup", true), ("load_level", true), ("spawn", false)]
+    );
+
+    let load_level = find_system(&module, "load_level");
+    assert_eq!(load_level.inputs.len(), 1);
+    assert_eq!(load_level.inputs[0].ident, "Window");
+    assert!(!load_level.inputs[0].mutable);
+}
+
+#[test]
+fn queries() {
+    let module = parse_fixture("queries");
+
+    let integrate = find_system(&module, "integrate");
+    assert_eq!(integrate.inputs.len(), 2);
+    assert_eq!(
+        query_terms(integrate, 0),
+        [("Transform", true), ("Velocity", false)]
+    );
+    assert_eq!(integrate.inputs[1].ident, "Time");
+
+    // the declaration spans several lines
+    let render_health = find_system(&module, "render_health");
+    assert_eq!(render_health.source.line, 16);
+    assert_eq!(render_health.inputs.len(), 3);
+    assert_eq!(
+        query_terms(render_health, 0),
+        [("Transform", false), ("Health", false), ("Color", true)]
+    );
+    assert_eq!(query_terms(render_health, 1), [("Camera", false)]);
+    assert_eq!(render_health.inputs[2].ident, "Window");
+
+    assert!(module.validate().is_ok());
+}
+
+#[test]
+fn errors() {
+    let module = parse_fixture("errors");
+
+    assert_eq!(
+        find_system(&module, "regenerate").return_type,
+        ReturnType::Bool
+    );
+    assert_eq!(
+        find_system(&module, "spawn_waves").return_type,
+        ReturnType::Enum {
+            ident: String::from("SpawnError"),
+            variants: vec![
+                String::from("Ok"),
+                String::from("NoSpace"),
+                String::from("Full"),
+                String::from("TooMany")
+            ],
+        }
+    );
+    assert_eq!(
+        find_system(&module, "check_health").return_type,
+        ReturnType::AreteResult
+    );
+    assert_eq!(find_system(&module, "setup").return_type, ReturnType::Void);
+
+    assert!(module.validate().is_ok());
+}
+
+#[test]
+fn empty() {
+    let module = parse_fixture("empty");
+
+    assert!(module.structs.is_empty());
+    assert!(module.systems.is_empty());
+    assert!(module.validate().is_ok());
+}
+
+#[test]
+fn resource_query() {
+    let module = parse_fixture("resource_query");
+
+    let tally = find_system(&module, "tally");
+    assert_eq!(query_terms(tally, 0), [("Score", true)]);
+    assert!(module.validate().is_err());
+}
+
+// generating validates first rather than panicking on the lookups validation guarantees
+#[test]
+fn generate_unvalidated() {
+    let module = parse_fixture("undeclared_component");
+
+    for backend in [Backend::CHeader, Backend::RustBindings, Backend::Manifest] {
+        assert!(matches!(
+            module.generate(&backend),
+            Err(Error::Invalid { .. })
+        ));
+    }
+}
+
+#[test]
+fn direct_component() {
+    let module = parse_fixture("direct_component");
+
+    let accelerate = find_system(&module, "accelerate");
+    assert!(matches!(
+        accelerate.inputs[0].arg_type,
+        ArgType::DataAccessDirect
+    ));
+    assert!(module.validate().is_err());
+}
+
+#[test]
+fn undeclared_component() {
+    let module = parse_fixture("undeclared_component");
+
+    let integrate = find_system(&module, "integrate");
+    assert_eq!(
+        query_terms(integrate, 0),
+        [("Transform", true), ("Velocity", false)]
+    );
+    assert!(module.validate().is_err());
+}
+
+#[test]
+fn by_value_param() {
+    assert_eq!(
+        parse_error("by_value_param"),
+        (
+            10,
+            String::from("parameter `Gravity gravity` of fall must be taken as a reference")
+        )
+    );
+}
+
+#[test]
+fn unterminated_query() {
+    assert_eq!(
+        parse_error("unterminated_query"),
+        (
+            10,
+            String::from("malformed query `Query<Transform&, const Velocity& movers`")
+        )
+    );
+}
+
+#[test]
+fn duplicate_component() {
+    let module = parse_fixture("duplicate_component");
+
+    let idents: Vec<_> = module.structs.iter().map(|s| s.ident.as_str()).collect();
+    assert_eq!(idents, ["Velocity", "Velocity"]);
+
+    let Err(Error::Invalid { source, message }) = module.validate() else {
+        panic!("duplicate component wasn't rejected");
+    };
+    assert_eq!(source.line, 5);
+    assert_eq!(message, "game_module::Velocity is declared more than once");
+}